@@ -5,7 +5,7 @@ fn send_x_from_1_to_m(base: usize, n: usize, m: usize) {
 
     let mut rx_t = Vec::with_capacity(m);
     for _ in 0..m {
-        let mut rx = rx.clone();
+        let rx = rx.clone();
         rx_t.push(std::thread::spawn(move || {
             let mut prev = 0;
             loop {
@@ -0,0 +1,204 @@
+//! Configuration of a slot, see [`Builder`]
+use crate::{
+    atomic, latency::Observations, limit::Limit, membarrier::Fences, metrics::Metrics,
+    policy::Eager, reclaimer::Background, Arc, Epochs, Monitor, Mutex, Node, Prevs, RcuObserver,
+    Reader, ReclaimPolicy, Reclaimer, Retire, RetireFn, Shared, StallDetector, Weigher, Writer,
+};
+use std::{cell::UnsafeCell, time::Instant};
+
+/// Configures and creates a slot
+///
+/// `Builder::new().slot(v)` is equivalent to [`crate::slot()`].
+pub struct Builder<T> {
+    retire: Option<RetireFn<T>>,
+    reclaimer: Option<(Reclaimer, SpawnFn<T>)>,
+    policy: Box<dyn ReclaimPolicy + Send>,
+    limit: Option<Limit<T>>,
+    stall: Option<StallDetector>,
+    metrics: bool,
+    observer: Option<Box<dyn RcuObserver + Send + Sync>>,
+    observations: Option<usize>,
+    membarrier: bool,
+}
+
+/// `Background::spawn()` for a `T` known to be `Send + 'static`
+type SpawnFn<T> = fn(&Reclaimer, Option<RetireFn<T>>, &Shared<T>) -> Background<T>;
+
+impl<T> Default for Builder<T> {
+    fn default() -> Self {
+        Builder::new()
+    }
+}
+
+impl<T> Builder<T> {
+    /// Create a `Builder` with the default configuration
+    pub fn new() -> Builder<T> {
+        Builder {
+            retire: None,
+            reclaimer: None,
+            policy: Box::new(Eager),
+            limit: None,
+            stall: None,
+            metrics: false,
+            observer: None,
+            observations: None,
+            membarrier: false,
+        }
+    }
+
+    /// Pass old values to `retire` instead of returning them
+    ///
+    /// See [`Retire`].
+    pub fn retire<R: Retire<T> + Send + 'static>(mut self, retire: R) -> Builder<T> {
+        self.retire = Some(Box::new(retire));
+        self
+    }
+
+    /// Decide when `Writer::write()` scans for old values, see [`ReclaimPolicy`]
+    ///
+    /// Kept by [`Writer::into_shared()`], where it decides when `SharedWriter::write()` scans.
+    /// Ignored when there is a [`Reclaimer`].
+    pub fn reclaim_policy<P: ReclaimPolicy + Send + 'static>(mut self, policy: P) -> Builder<T> {
+        self.policy = Box::new(policy);
+        self
+    }
+
+    /// Limit the number of old values waiting to be collected to `max`
+    ///
+    /// Enforced by [`Writer::try_write()`] & [`Writer::write_bounded()`] (and the same methods of
+    /// a [`crate::SharedWriter`]), other writes ignore the limit. At least 1 old value is always
+    /// allowed.
+    pub fn max_pending(mut self, max: usize) -> Builder<T> {
+        self.limit = Some(Limit::count(max));
+        self
+    }
+
+    /// Limit the total weight of old values waiting to be collected to `max`, as measured by
+    /// `weigher`
+    ///
+    /// Like `max_pending()`, but each value counts as `weigher.weigh(value)` instead of 1. A
+    /// single value heavier than `max` is allowed if no other old values are pending.
+    pub fn max_pending_weight<W: Weigher<T> + Send + 'static>(
+        mut self,
+        max: usize,
+        weigher: W,
+    ) -> Builder<T> {
+        self.limit = Some(Limit::weight(max, Box::new(weigher)));
+        self
+    }
+
+    /// Collect old values on a background thread, see [`Reclaimer`]
+    pub fn reclaimer(mut self, reclaimer: Reclaimer) -> Builder<T>
+    where
+        T: Send + 'static,
+    {
+        self.reclaimer = Some((reclaimer, Background::spawn));
+        self
+    }
+
+    /// Report readers that keep old values in use for too long, see [`StallDetector`]
+    pub fn stall_detector(mut self, stall: StallDetector) -> Builder<T> {
+        self.stall = Some(stall);
+        self
+    }
+
+    /// Count writes, reclaimed values, reader registrations and guard hold times, see
+    /// [`crate::Writer::metrics()`]
+    ///
+    /// Disabled by default. When enabled, each write & scan does a few relaxed atomic increments
+    /// and entering or leaving a read section reads the clock.
+    pub fn metrics(mut self, enabled: bool) -> Builder<T> {
+        self.metrics = enabled;
+        self
+    }
+
+    /// Call `observer` on each lifecycle event of the slot, see [`RcuObserver`]
+    pub fn observer<O: RcuObserver + Send + Sync + 'static>(mut self, observer: O) -> Builder<T> {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    /// Record when readers first observe each of the last `versions` published values, see
+    /// [`crate::Writer::observations()`]
+    ///
+    /// Disabled by default. When enabled, the first read of each new value by a reader reads the
+    /// clock and updates a few atomics shared with the other readers.
+    pub fn track_observations(mut self, versions: usize) -> Builder<T> {
+        self.observations = Some(versions);
+        self
+    }
+
+    /// Use `membarrier()` so readers don't need a full fence each time they enter a read section
+    ///
+    /// Disabled by default. When enabled, readers only use a compiler fence, and each write has
+    /// the kernel run a full memory barrier on every running thread of the process instead, which
+    /// is much slower than a fence. Worth it when reads greatly outnumber writes.
+    ///
+    /// Only available on Linux 4.14 and later (`MEMBARRIER_CMD_PRIVATE_EXPEDITED`). Elsewhere, or
+    /// if the system call is not permitted, full fences are used as if this was disabled. See
+    /// [`Writer::uses_membarrier()`].
+    ///
+    /// This relies on the kernel's guarantees for `membarrier()`, which this crate's loom model
+    /// checking can't express: its models treat both the readers' compiler fence and the writer's
+    /// `membarrier()` as full fences, so they only check where the writer issues `membarrier()`.
+    pub fn membarrier(mut self, enabled: bool) -> Builder<T> {
+        self.membarrier = enabled;
+        self
+    }
+
+    /// Create a `Writer` with an initial value
+    pub fn build(self, init_val: Box<T>) -> Writer<T> {
+        let now = Instant::now();
+        let shared = Arc::new(Shared {
+            active: atomic::AtomicPtr::new(Box::into_raw(Box::new(Node {
+                value: init_val,
+                version: 0,
+                published_at: now,
+            }))),
+            epochs: Arc::new(Epochs::new()),
+            prevs: UnsafeCell::new(Prevs::new()),
+            version: atomic::AtomicU64::new(0),
+            closed: atomic::AtomicBool::new(false),
+            wakers: Mutex::new(slab::Slab::new()),
+            waiting: atomic::AtomicUsize::new(0),
+            notify_pending: atomic::AtomicBool::new(false),
+            sync_waiting: atomic::AtomicBool::new(false),
+            sync_waiter: Mutex::new(None),
+            monitor: Arc::new(Monitor {
+                stall: self.stall,
+                metrics: self.metrics.then(Metrics::default),
+                observer: self.observer,
+                observations: self
+                    .observations
+                    .map(|versions| Observations::new(versions, now)),
+            }),
+            fences: Fences::new(self.membarrier),
+        });
+
+        match self.reclaimer {
+            Some((config, spawn)) => Writer {
+                reclaimer: Some(spawn(&config, self.retire, &shared)),
+                shared,
+                retire: None,
+                policy: self.policy,
+                limit: self.limit,
+                drained: Vec::new(),
+            },
+            None => Writer {
+                shared,
+                retire: self.retire,
+                reclaimer: None,
+                policy: self.policy,
+                limit: self.limit,
+                drained: Vec::new(),
+            },
+        }
+    }
+
+    /// Create a new slot containing an initial value `init_val`
+    pub fn slot(self, init_val: T) -> (Writer<T>, Reader<T>) {
+        let w = self.build(Box::new(init_val));
+        let r = w.reader();
+        (w, r)
+    }
+}
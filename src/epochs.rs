@@ -0,0 +1,321 @@
+//! Registry of the readers' epoch counters, see [`Epochs`]
+use crate::{atomic, metrics::Holds, stall::ReaderInfo, StallDetector};
+use std::{
+    array, ptr,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+};
+
+/// Number of slots in the first chunk. Each following chunk is twice the size of the previous one.
+const FIRST_CHUNK: usize = 8;
+
+/// Enough chunks for `FIRST_CHUNK << CHUNKS` readers.
+const CHUNKS: usize = 32;
+
+/// `Epoch::state` of a slot with no reader
+const FREE: u8 = 0;
+/// `Epoch::state` of a slot with a reader
+const IN_USE: u8 = 1;
+/// `Epoch::state` of a slot whose reader was dropped in a read section. Never reused.
+const LEAKED: u8 = 2;
+
+/// A reader's epoch counter, along with what we know about the reader
+///
+/// Each slot is padded to 128 bytes (2 cache lines, as adjacent lines are often prefetched
+/// together) so readers updating their epoch never contend with each other.
+#[repr(align(128))]
+pub(crate) struct Epoch {
+    /// Odd while the reader is in a read section.
+    ///
+    /// Never reset, so it keeps increasing when the slot is reused by a new reader. A snapshot of
+    /// an odd value therefore can't be matched by a later reader of the same slot.
+    pub(crate) value: atomic::AtomicUsize,
+    /// `FREE`, `IN_USE` or `LEAKED`.
+    state: atomic::AtomicU8,
+    /// Next slot in `Epochs::free` (plus 1, 0 for none) while this slot is free.
+    next_free: atomic::AtomicUsize,
+    /// Incremented each time the slot is handed to a new reader.
+    generation: AtomicU64,
+    /// `value` when the current reader was registered.
+    base: AtomicUsize,
+    /// Identifies the reader in `StallDetector` reports.
+    pub(crate) info: ReaderInfo,
+    /// Only updated when metrics are enabled.
+    pub(crate) holds: Holds,
+}
+
+impl Epoch {
+    fn new() -> Epoch {
+        Epoch {
+            value: atomic::AtomicUsize::new(0),
+            state: atomic::AtomicU8::new(FREE),
+            next_free: atomic::AtomicUsize::new(0),
+            generation: AtomicU64::new(0),
+            base: AtomicUsize::new(0),
+            info: ReaderInfo::new(),
+            holds: Holds::default(),
+        }
+    }
+
+    /// Number of read sections the current reader has entered, including the current one
+    pub(crate) fn reads(&self) -> usize {
+        let v = self
+            .value
+            .load(atomic::Ordering::Relaxed)
+            .wrapping_sub(self.base.load(Ordering::Relaxed));
+        // Each read section increments the epoch twice, the first time on entry.
+        v / 2 + (v & 1)
+    }
+}
+
+/// Epoch slots for all of a slot's readers
+///
+/// Slots live in chunks that are never moved or freed until the `Epochs` is dropped, so readers
+/// and snapshots can refer to a slot by index (or a reader by pointer). Slots of dropped readers
+/// are kept on a free list and reused by new readers.
+///
+/// Registering and unregistering readers is lock free (unless a `StallDetector` captures a
+/// backtrace), and doesn't block (or get blocked by) writers scanning the slots.
+pub(crate) struct Epochs {
+    /// Chunk `k` holds `FIRST_CHUNK << k` slots. Null until first needed.
+    chunks: [atomic::AtomicPtr<Epoch>; CHUNKS],
+    /// Number of slots handed out, some of which may be in chunks that are still being allocated.
+    len: atomic::AtomicUsize,
+    /// Top of the free list: the index of a free slot plus 1 (0 if the list is empty) in the low
+    /// 32 bits, and a counter in the high 32 bits that is bumped on every change so a concurrent
+    /// pop & push of the same slot (ABA) makes a stale compare-exchange fail.
+    free: atomic::AtomicU64,
+    /// Number of slots in use.
+    readers: AtomicUsize,
+}
+
+/// Chunk & offset of slot `index`
+fn locate(index: usize) -> (usize, usize) {
+    let n = index + FIRST_CHUNK;
+    let chunk = (n.ilog2() - FIRST_CHUNK.ilog2()) as usize;
+    (chunk, n - (FIRST_CHUNK << chunk))
+}
+
+impl Epochs {
+    pub(crate) fn new() -> Epochs {
+        Epochs {
+            chunks: array::from_fn(|_| atomic::AtomicPtr::new(ptr::null_mut())),
+            len: atomic::AtomicUsize::new(0),
+            free: atomic::AtomicU64::new(0),
+            readers: AtomicUsize::new(0),
+        }
+    }
+
+    /// Slot `index`, if its chunk has been allocated
+    fn try_get(&self, index: usize) -> Option<&Epoch> {
+        let (chunk, offset) = locate(index);
+        // Pairs with the `AcqRel` in `alloc()`, so the chunk is initialized.
+        let chunk = self.chunks[chunk].load(atomic::Ordering::Acquire);
+        // SAFETY: chunks are never freed while we exist, and `offset` is within the chunk.
+        (!chunk.is_null()).then(|| unsafe { &*chunk.add(offset) })
+    }
+
+    /// Slot `index`, which must have been returned by `register()`
+    pub(crate) fn get(&self, index: usize) -> &Epoch {
+        self.try_get(index).expect("epoch slot not allocated")
+    }
+
+    /// Hand out a slot that has never been used
+    fn alloc(&self) -> usize {
+        let index = self.len.fetch_add(1, atomic::Ordering::Relaxed);
+        let (chunk, _) = locate(index);
+        assert!(chunk < CHUNKS, "too many readers");
+        if self.try_get(index).is_none() {
+            // Whoever claims a slot in a chunk first allocates it, if more than 1 do the losers
+            // free theirs.
+            let slots: Box<[Epoch]> = (0..FIRST_CHUNK << chunk).map(|_| Epoch::new()).collect();
+            let slots = Box::into_raw(slots) as *mut Epoch;
+            if self.chunks[chunk]
+                .compare_exchange(
+                    ptr::null_mut(),
+                    slots,
+                    atomic::Ordering::AcqRel,
+                    atomic::Ordering::Acquire,
+                )
+                .is_err()
+            {
+                // SAFETY: we just created this, and never shared it.
+                drop(unsafe {
+                    Box::from_raw(ptr::slice_from_raw_parts_mut(slots, FIRST_CHUNK << chunk))
+                });
+            }
+        }
+        index
+    }
+
+    /// Take a slot from the free list
+    fn pop_free(&self) -> Option<usize> {
+        let mut head = self.free.load(atomic::Ordering::Acquire);
+        loop {
+            let top = (head & u64::from(u32::MAX)) as usize;
+            if top == 0 {
+                return None;
+            }
+            // If another thread pops this slot first, `next` may be garbage, but `head` will have
+            // changed so the exchange fails.
+            let next = self.get(top - 1).next_free.load(atomic::Ordering::Relaxed);
+            let new = (head >> 32).wrapping_add(1) << 32 | next as u64;
+            match self.free.compare_exchange_weak(
+                head,
+                new,
+                atomic::Ordering::Acquire,
+                atomic::Ordering::Acquire,
+            ) {
+                Ok(_) => return Some(top - 1),
+                Err(actual) => head = actual,
+            }
+        }
+    }
+
+    /// Put a slot on the free list
+    fn push_free(&self, index: usize) {
+        let epoch = self.get(index);
+        let mut head = self.free.load(atomic::Ordering::Relaxed);
+        loop {
+            epoch.next_free.store(
+                (head & u64::from(u32::MAX)) as usize,
+                atomic::Ordering::Relaxed,
+            );
+            let new = (head >> 32).wrapping_add(1) << 32 | (index + 1) as u64;
+            // `Release` pairs with the `Acquire` in `pop_free()`, so the next reader of the slot
+            // sees everything the last one did.
+            match self.free.compare_exchange_weak(
+                head,
+                new,
+                atomic::Ordering::Release,
+                atomic::Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(actual) => head = actual,
+            }
+        }
+    }
+
+    /// Hand a slot to a new reader, returning its index
+    pub(crate) fn register(&self, stall: Option<&StallDetector>) -> usize {
+        let index = self.pop_free().unwrap_or_else(|| self.alloc());
+        assert!(index < u32::MAX as usize, "too many readers");
+
+        let epoch = self.get(index);
+        let generation = epoch.generation.load(Ordering::Relaxed);
+        epoch.generation.store(generation + 1, Ordering::Relaxed);
+        // The previous reader (if any) is gone, so nobody else is updating `value`.
+        epoch.base.store(
+            epoch.value.load(atomic::Ordering::Relaxed),
+            Ordering::Relaxed,
+        );
+        epoch.info.reset(stall);
+        epoch.holds.reset();
+        // Pairs with the `Acquire` in `for_each()`, so inspecting the slot sees the reset.
+        epoch.state.store(IN_USE, atomic::Ordering::Release);
+        self.readers.fetch_add(1, Ordering::Relaxed);
+        index
+    }
+
+    /// Release slot `index` when its reader is dropped, calling `f` on it first
+    ///
+    /// If the reader was `leaked` in a read section, the slot is never reused: its epoch stays
+    /// odd, so any values it was using are never collected.
+    pub(crate) fn unregister(&self, index: usize, leaked: bool, f: impl FnOnce(&Epoch)) {
+        let epoch = self.get(index);
+        f(epoch);
+        self.readers.fetch_sub(1, Ordering::Relaxed);
+        if leaked {
+            epoch.state.store(LEAKED, atomic::Ordering::Relaxed);
+        } else {
+            epoch.state.store(FREE, atomic::Ordering::Relaxed);
+            self.push_free(index);
+        }
+    }
+
+    /// Call `f` with each slot in use by a reader
+    ///
+    /// Readers may be created or dropped concurrently, and may or may not be included.
+    pub(crate) fn for_each(&self, mut f: impl FnMut(usize, &Epoch)) {
+        let len = self.len.load(atomic::Ordering::Relaxed);
+        for index in 0..len {
+            if let Some(epoch) = self.try_get(index) {
+                if epoch.state.load(atomic::Ordering::Acquire) == IN_USE {
+                    f(index, epoch);
+                }
+            }
+        }
+    }
+
+    /// Number of readers
+    pub(crate) fn count(&self) -> usize {
+        self.readers.load(Ordering::Relaxed)
+    }
+
+    /// Snapshot the readers currently in a read section, sorted by slot index
+    ///
+    /// A reader concurrently entering a read section may be missed, in which case it is ordered
+    /// by the caller's `Fences::heavy()` (paired with `Fences::light()` in `Reader::lock()`) to
+    /// see the value that was just published.
+    ///
+    /// `holdouts` is cleared first, so its buffer can be reused.
+    pub(crate) fn holdouts(&self, holdouts: &mut Vec<Holdout>) {
+        holdouts.clear();
+        let len = self.len.load(atomic::Ordering::Relaxed);
+        for index in 0..len {
+            // A slot in a chunk that isn't allocated yet has no reader.
+            let Some(epoch) = self.try_get(index) else {
+                continue;
+            };
+            // This pairs with a `Release` in `Reader::read()`, which ensures all the reads by the
+            // reader are retired. We don't need to see the writes done by the caller of
+            // `Reader::read()`, so `Relaxed` is sufficient (`Acquire` would ensure we see
+            // writes).
+            //
+            // Free slots are never odd. Leaked slots are, but the values they were using are
+            // already held forever, no need to hold new ones.
+            let v = epoch.value.load(atomic::Ordering::Relaxed);
+            if v & 1 != 0 && epoch.state.load(atomic::Ordering::Relaxed) != LEAKED {
+                holdouts.push(Holdout {
+                    index,
+                    generation: epoch.generation.load(Ordering::Relaxed),
+                    epoch: v,
+                });
+            }
+        }
+    }
+}
+
+impl Drop for Epochs {
+    fn drop(&mut self) {
+        for (chunk, slots) in self.chunks.iter().enumerate() {
+            let slots = slots.load(atomic::Ordering::Relaxed);
+            if slots.is_null() {
+                continue;
+            }
+            // SAFETY: created from a `Box<[Epoch]>` of this length in `alloc()`.
+            drop(unsafe {
+                Box::from_raw(ptr::slice_from_raw_parts_mut(slots, FIRST_CHUNK << chunk))
+            });
+        }
+    }
+}
+
+/// A reader that was in a read section when a value was replaced, see `prevs::Batch::readers`
+#[derive(PartialEq, Eq)]
+pub(crate) struct Holdout {
+    /// Slot of the reader in `Epochs`.
+    pub(crate) index: usize,
+    /// Generation of the slot, identifies the reader.
+    generation: u64,
+    /// The reader's epoch at the time.
+    epoch: usize,
+}
+
+impl Holdout {
+    /// Is the reader still in the same read section?
+    pub(crate) fn is_held(&self, epochs: &Epochs) -> bool {
+        let epoch = epochs.get(self.index);
+        epoch.value.load(atomic::Ordering::Relaxed) == self.epoch
+            && epoch.generation.load(Ordering::Relaxed) == self.generation
+    }
+}
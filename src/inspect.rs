@@ -0,0 +1,67 @@
+//! Inspecting the state of a slot, see [`Writer::readers()`] & [`Writer::pending()`]
+use crate::{atomic, Writer};
+
+/// State of a registered reader, returned by [`Writer::readers()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReaderStatus {
+    /// Set with [`crate::Reader::set_name()`]
+    pub name: Option<String>,
+    /// Is the reader currently in a read section (holding a `ReadGuard` or similar)?
+    pub in_read_section: bool,
+    /// Number of read sections the reader has entered, including the current one. Nested read
+    /// sections are not counted.
+    pub reads: usize,
+}
+
+/// An old value waiting to be collected, returned by [`Writer::pending()`]
+#[derive(Debug)]
+pub struct PendingValue<'a, T> {
+    /// The old value
+    pub value: &'a T,
+    /// Version of the old value
+    pub version: u64,
+    /// Number of readers that may still be using the value
+    pub readers: usize,
+}
+
+impl<T> Writer<T> {
+    /// Number of `Reader`s that currently exist
+    pub fn reader_count(&self) -> usize {
+        self.shared.epochs.count()
+    }
+
+    /// State of each `Reader` that currently exists, in no particular order
+    ///
+    /// This is a snapshot: readers may enter or leave read sections at any time.
+    pub fn readers(&self) -> impl Iterator<Item = ReaderStatus> {
+        let mut readers = Vec::new();
+        self.shared.epochs.for_each(|_, epoch| {
+            readers.push(ReaderStatus {
+                name: epoch.info.name(),
+                in_read_section: epoch.value.load(atomic::Ordering::Relaxed) & 1 != 0,
+                reads: epoch.reads(),
+            })
+        });
+        readers.into_iter()
+    }
+
+    /// The old values waiting to be collected, with the number of readers that may still be
+    /// using each
+    ///
+    /// A value with no readers will be collected by the next `try_sync()`. Values handed to a
+    /// [`crate::Reclaimer`] are not included.
+    pub fn pending(&self) -> impl Iterator<Item = PendingValue<'_, T>> {
+        self.prevs().batches().iter().flat_map(|batch| {
+            let readers = batch
+                .readers
+                .iter()
+                .filter(|holdout| holdout.is_held(&self.shared.epochs))
+                .count();
+            batch.values.iter().map(move |retired| PendingValue {
+                value: &*retired.node.value,
+                version: retired.node.version,
+                readers,
+            })
+        })
+    }
+}
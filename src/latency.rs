@@ -0,0 +1,141 @@
+//! Measuring how long readers take to pick up new values, see [`Observation`]
+use crate::Writer;
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// `Slot::version` while the slot is being reset
+const EMPTY: u64 = u64::MAX;
+
+/// When readers first observed each of the most recently published values
+///
+/// Enabled with [`crate::Builder::track_observations()`]. Times are stored as nanoseconds since
+/// `start`.
+pub(crate) struct Observations {
+    start: Instant,
+    /// Version `v` is recorded in `slots[v % slots.len()]`.
+    slots: Box<[Slot]>,
+}
+
+struct Slot {
+    version: AtomicU64,
+    published: AtomicU64,
+    first: AtomicU64,
+    last: AtomicU64,
+    readers: AtomicU64,
+}
+
+impl Observations {
+    /// Track the last `versions` published values, starting with the initial value published at
+    /// `start`
+    pub(crate) fn new(versions: usize, start: Instant) -> Observations {
+        let slots = (0..versions.max(1))
+            .map(|_| Slot {
+                version: AtomicU64::new(EMPTY),
+                published: AtomicU64::new(0),
+                first: AtomicU64::new(u64::MAX),
+                last: AtomicU64::new(0),
+                readers: AtomicU64::new(0),
+            })
+            .collect();
+        let observations = Observations { start, slots };
+        observations.published(0, start);
+        observations
+    }
+
+    fn slot(&self, version: u64) -> &Slot {
+        &self.slots[(version % self.slots.len() as u64) as usize]
+    }
+
+    fn nanos(&self, at: Instant) -> u64 {
+        u64::try_from(at.saturating_duration_since(self.start).as_nanos()).unwrap_or(u64::MAX)
+    }
+
+    /// Start recording observations of `version`, replacing the oldest version tracked
+    ///
+    /// Only called by the writer, before `version` is visible to readers.
+    pub(crate) fn published(&self, version: u64, at: Instant) {
+        let slot = self.slot(version);
+        slot.version.store(EMPTY, Ordering::Relaxed);
+        slot.published.store(self.nanos(at), Ordering::Relaxed);
+        slot.first.store(u64::MAX, Ordering::Relaxed);
+        slot.last.store(0, Ordering::Relaxed);
+        slot.readers.store(0, Ordering::Relaxed);
+        // Pairs with the `Acquire` in `observed()` & `get()`: the reset is visible before the
+        // slot is claimed for `version`.
+        slot.version.store(version, Ordering::Release);
+    }
+
+    /// A reader observed `version` for the first time
+    ///
+    /// If `version` is no longer tracked, the observation is dropped. A reader that is preempted
+    /// here while as many new values as are tracked are published may record its observation
+    /// against a newer version.
+    pub(crate) fn observed(&self, version: u64, at: Instant) {
+        let slot = self.slot(version);
+        if slot.version.load(Ordering::Acquire) != version {
+            return;
+        }
+        let at = self.nanos(at);
+        slot.first.fetch_min(at, Ordering::Relaxed);
+        slot.last.fetch_max(at, Ordering::Relaxed);
+        slot.readers.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn get(&self, version: u64) -> Option<Observation> {
+        let slot = self.slot(version);
+        if slot.version.load(Ordering::Acquire) != version {
+            return None;
+        }
+        let published = slot.published.load(Ordering::Relaxed);
+        let first = slot.first.load(Ordering::Relaxed);
+        let last = slot.last.load(Ordering::Relaxed);
+        let readers = slot.readers.load(Ordering::Relaxed);
+        let latency = |at: u64| Duration::from_nanos(at.saturating_sub(published));
+        Some(Observation {
+            version,
+            published_at: self.start + Duration::from_nanos(published),
+            readers,
+            first: (readers != 0).then(|| latency(first)),
+            last: (readers != 0).then(|| latency(last)),
+        })
+    }
+}
+
+/// When readers picked up a published value, returned by [`Writer::observations()`]
+///
+/// A reader observes a value the first time one of its reads returns it. Readers that never read
+/// the value (because they didn't read before it was replaced, or were created after it was
+/// published) are not counted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Observation {
+    /// Version of the value
+    pub version: u64,
+    /// When the value was published, see [`crate::ReadGuard::published_at()`]
+    pub published_at: Instant,
+    /// Number of readers that have observed the value
+    pub readers: u64,
+    /// Time from publishing until the first reader observed the value, `None` if no reader has
+    pub first: Option<Duration>,
+    /// Time from publishing until the most recent reader observed the value, `None` if no reader
+    /// has
+    pub last: Option<Duration>,
+}
+
+impl<T> Writer<T> {
+    /// Observations of the most recently published values, newest first
+    ///
+    /// Empty unless enabled with [`crate::Builder::track_observations()`], which also sets how
+    /// many values are included. The `first` & `last` latencies across versions form the
+    /// distribution of how long readers take to pick up new values.
+    pub fn observations(&self) -> impl Iterator<Item = Observation> + '_ {
+        let observations = self.shared.monitor.observations.as_ref();
+        let tracked = observations.map_or(0, |o| o.slots.len() as u64);
+        let version = self.version();
+        (version.saturating_sub(tracked.saturating_sub(1))..=version)
+            .rev()
+            .take(tracked as usize)
+            .filter_map(move |version| observations?.get(version))
+    }
+}
@@ -0,0 +1,140 @@
+//! Two copies of a value kept in step with an operation log, see [`LeftRight`]
+use crate::{Reader, Writer};
+
+/// Applies operations of type `O` to a value
+///
+/// Used by [`LeftRight`] to keep its 2 copies in step. Each operation is applied to both copies,
+/// first with `absorb_first()` to the copy about to be published, then (once readers have moved
+/// on from it) with `absorb_second()` to the other copy.
+///
+/// Both must have the same effect, otherwise the copies will diverge.
+pub trait Absorb<O> {
+    /// Apply `op` to the first of the 2 copies
+    ///
+    /// `other` is the currently published copy, which readers may be using.
+    fn absorb_first(&mut self, op: &mut O, other: &Self);
+
+    /// Apply `op` to the second of the 2 copies
+    ///
+    /// `op` was passed to `absorb_first()` earlier, and is dropped afterwards. Defaults to calling
+    /// `absorb_first()`.
+    fn absorb_second(&mut self, mut op: O, other: &Self) {
+        self.absorb_first(&mut op, other)
+    }
+}
+
+/// Writer for a slot that keeps exactly 2 copies of the value, updated by appending operations
+///
+/// Created by [`crate::left_right()`] or [`LeftRight::new()`]. Operations are added to a log with
+/// `append()`, and are made visible to readers by `publish()`, which applies them (see [`Absorb`])
+/// to the copy readers are not using and then swaps the copies. Unlike `Writer::write()`, memory
+/// use is bounded: publishing waits for (or with `try_publish()`, gives up on) readers still
+/// using the old copy instead of allocating a third one.
+///
+/// Readers are plain [`Reader`]s.
+pub struct LeftRight<T, O> {
+    writer: Writer<T>,
+    /// The unpublished copy, once no reader is using it.
+    spare: Option<Box<T>>,
+    /// The first `absorbed` operations have been applied to the published copy only, the rest
+    /// have not been applied to either copy.
+    oplog: Vec<O>,
+    absorbed: usize,
+}
+
+impl<T: Absorb<O> + Clone, O> LeftRight<T, O> {
+    /// Create a `LeftRight` with an initial value
+    ///
+    /// The second copy is cloned from `init_val`.
+    pub fn new(init_val: T) -> LeftRight<T, O> {
+        let spare = Box::new(init_val.clone());
+        LeftRight {
+            writer: Writer::new(Box::new(init_val)),
+            spare: Some(spare),
+            oplog: Vec::new(),
+            absorbed: 0,
+        }
+    }
+}
+
+impl<T: Absorb<O>, O> LeftRight<T, O> {
+    /// Obtain a reader for the published copy
+    ///
+    /// See [`Writer::reader()`].
+    pub fn reader(&self) -> Reader<T> {
+        self.writer.reader()
+    }
+
+    /// Read the published copy
+    ///
+    /// Operations that were appended but not yet published are not visible here.
+    pub fn read(&self) -> &T {
+        self.writer.read()
+    }
+
+    /// Version of the published copy, see [`Writer::version()`]
+    pub fn version(&self) -> u64 {
+        self.writer.version()
+    }
+
+    /// Add an operation to the log, it will be applied by the next `publish()`
+    pub fn append(&mut self, op: O) {
+        self.oplog.push(op);
+    }
+
+    /// Are there operations that have not been published yet?
+    pub fn has_pending(&self) -> bool {
+        self.oplog.len() > self.absorbed
+    }
+
+    /// Apply all appended operations and publish the result
+    ///
+    /// Blocks until no reader is using the unpublished copy, see [`Writer::sync()`]. Does nothing
+    /// if no operations were appended since the last publish, so readers aren't told about a new
+    /// version with the same value.
+    pub fn publish(&mut self) {
+        if !self.has_pending() {
+            return;
+        }
+        if self.spare.is_none() {
+            self.spare = self.writer.sync().pop();
+        }
+        self.publish_spare();
+    }
+
+    /// Like `publish()`, but returns `false` without publishing if a reader is still using the
+    /// unpublished copy
+    ///
+    /// Operations stay in the log and are published by a later call. Returns `true` without
+    /// publishing if there are no operations to publish.
+    pub fn try_publish(&mut self) -> bool {
+        if !self.has_pending() {
+            return true;
+        }
+        if self.spare.is_none() {
+            self.spare = self.writer.try_sync_drain().next();
+            if self.spare.is_none() {
+                return false;
+            }
+        }
+        self.publish_spare();
+        true
+    }
+
+    fn publish_spare(&mut self) {
+        let mut spare = self.spare.take().expect("spare copy is missing");
+        let published = self.writer.read();
+
+        // Catch up on the operations that were only applied to `published`...
+        for op in self.oplog.drain(..self.absorbed) {
+            spare.absorb_second(op, published);
+        }
+        // ...and apply the new ones, keeping them to apply to `published` next time.
+        for op in &mut self.oplog {
+            spare.absorb_first(op, published);
+        }
+        self.absorbed = self.oplog.len();
+
+        self.writer.write_nosync(spare);
+    }
+}
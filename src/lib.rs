@@ -4,44 +4,190 @@
 //! One (1) writer provides a singe value to multiple readers. Readers _can_ see different versions
 //! of the datastructure simultaniously unless other synchronization is used.
 //!
-//! No atomic exchange, compare-and-swap, math operations are used, only loads &
+//! Reading & publishing use no atomic exchange, compare-and-swap or math operations, only loads &
 //! stores (most relaxed, with an Acquire in the reader (per read) and a Release in the
-//! writer (per write)).
+//! writer (per write)). Read-modify-write atomics are used when registering, naming & dropping
+//! readers (a compare-and-swap loop on a free list of epoch slots, swaps of the name, and
+//! counters), by `Reader::changed()` (including waking its waiters after a value is published),
+//! when leaving a read section wakes a writer waiting in `sync()` (a `try_lock()`), and by the
+//! optional metrics & observation tracking.
 //!
 //! - Reading wait free: only atomics are loads & stores. 1 atomic relaxed rmw
 //!   of no-contention data (only 1 writer), 1 atomic Acquire load of shared data.
-//! - Writing aquires an internal mutex to scan for old values to retire. Using
-//!   `write_nosync()` (which does not collect old is wait free.
-//! - Creating additional readers aquires an internal mutex & clones some `Arc`s.
+//! - Writing scans for old values to retire. A `Writer` without a `StallDetector` or `Reclaimer`
+//!   does so without taking any locks (waking `changed()` waiters only tries one). Otherwise
+//!   writing may block: a `StallDetector` locks its report function for each report, and a
+//!   `Reclaimer` locks its queue to hand over old values. A `SharedWriter` takes a lock for every
+//!   write, and another to scan. `write_nosync()` (which does not collect old values) is wait
+//!   free: it never takes a lock shared with readers, and only loops over a bounded number of
+//!   reader slots. How often `write()` scans can be tuned with a `ReclaimPolicy` (see
+//!   `Builder::reclaim_policy()`).
+//! - Creating additional readers clones an `Arc` and is lock free (unless a `StallDetector`
+//!   captures their backtraces), as is dropping them. Each reader's epoch lives in its own
+//!   cache-line padded slot, which is reused once the reader is dropped. Writes record which
+//!   readers are active as plain integers (slot index, generation & epoch). Consecutive old values
+//!   waiting on the same readers share a single record, and are collected together.
 //! - Returning previously written values is deferred. When using `write()`, old
 //!   values are automatically examined to determine if they may still be in use
 //!   by a reader. If they are definitely not in use by a reader, the old values
-//!   are returned.
+//!   are returned (or passed to a `Retire` configured with `Builder::retire()`).
+//! - Old values can be collected into a caller provided `Vec` (`Writer::write_into()`,
+//!   `Writer::try_sync_into()`) or drained from a buffer owned by the `Writer`
+//!   (`Writer::try_sync_drain()`). Internal bookkeeping is reused too, so a writer that keeps up
+//!   with its readers doesn't allocate anything other than the values it publishes.
+//! - Readers can wait for a new value to be published with `Reader::changed()`,
+//!   which is a `Future` usable with any executor.
+//! - `SharedWriter` (see `mpmc_slot()`) allows multiple producers. Publishing is serialized by an
+//!   internal mutex, collecting old values uses a seperate mutex.
+//! - With a `Reclaimer` (see `Builder::reclaimer()`), old values are collected on a background
+//!   thread and `write()` only publishes the new value.
+//! - `LeftRight` (see `left_right()`) keeps exactly 2 copies of a value, updated by appending
+//!   operations to a log that is applied to each copy with a user provided `Absorb`.
+//! - The old values waiting to be collected can be capped by count or total size (see
+//!   `Builder::max_pending()`), with `Writer::try_write()` failing and `Writer::write_bounded()`
+//!   waiting while a slow reader holds on to them.
+//! - A `StallDetector` (see `Builder::stall_detector()`) reports readers that keep old values in
+//!   use for too long.
+//! - Per-slot counters of writes, reclaimed values, reader registrations and guard hold times can
+//!   be enabled with `Builder::metrics()`, and exported in Prometheus format for one or many slots
+//!   (`MetricsSnapshot::write_prometheus()`).
+//! - Each value records when it was published (`ReadGuard::published_at()`), and how long readers
+//!   take to observe new values can be tracked with `Builder::track_observations()`.
+//! - An `RcuObserver` (see `Builder::observer()`) is called when values are published, retired &
+//!   reclaimed, and when readers are created & dropped.
+//! - With the `tracing` feature, writes, scans for old values, `sync()` and reader creation &
+//!   destruction emit `tracing` spans and events (target `local_rcu`).
+//! - Each published value is stamped with a version number, starting at `0` for the initial
+//!   value and incremented by 1 for each write.
+//! - On Linux, `Builder::membarrier()` moves the cost of the full fence in each read to the writer
+//!   (using `membarrier()`), so reading only needs a compiler fence.
+mod builder;
+mod epochs;
+mod inspect;
+mod latency;
+mod left_right;
+mod limit;
+mod membarrier;
+mod metrics;
+mod observer;
+pub mod policy;
+mod prevs;
+mod reclaimer;
+mod shared_writer;
+mod stall;
+
+pub use builder::Builder;
+use epochs::{Epoch, Epochs, Holdout};
+pub use inspect::{PendingValue, ReaderStatus};
+pub use latency::Observation;
+pub use left_right::{Absorb, LeftRight};
+pub use limit::Weigher;
 #[cfg(loom)]
 use loom::{
-    sync::{atomic, Arc, Mutex},
+    sync::{atomic, Arc, Condvar, Mutex},
     thread,
 };
-use std::{cell::UnsafeCell, marker::PhantomData, ops::Deref};
+pub use metrics::MetricsSnapshot;
+pub use observer::RcuObserver;
+pub use policy::ReclaimPolicy;
+use prevs::Prevs;
+pub use reclaimer::Reclaimer;
+pub use shared_writer::SharedWriter;
+pub use stall::{Stall, StallDetector, StalledReader};
+use std::{
+    cell::{Cell, UnsafeCell},
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    mem::{ManuallyDrop, MaybeUninit},
+    ops::Deref,
+    pin::Pin,
+    ptr,
+    sync::TryLockError,
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
 #[cfg(not(loom))]
 use std::{
-    sync::{atomic, Arc, Mutex},
+    sync::{atomic, Arc, Condvar, Mutex},
     thread,
 };
 
 /// Create a new SPMC slot containing an initial value `init_val`
+///
+/// Use [`Builder`] to configure the slot.
 pub fn slot<T>(init_val: T) -> (Writer<T>, Reader<T>) {
     let w = Writer::new(Box::new(init_val));
     let r = w.reader();
     (w, r)
 }
 
+/// Create a new MPMC (multiple producer, multiple consumer) slot containing an initial value
+/// `init_val`
+///
+/// The [`SharedWriter`] may be cloned to allow multiple threads to write values.
+pub fn mpmc_slot<T>(init_val: T) -> (SharedWriter<T>, Reader<T>) {
+    let (w, r) = slot(init_val);
+    (w.into_shared(), r)
+}
+
+/// Create a new slot with 2 copies of `init_val`, updated with operations of type `O`
+///
+/// See [`LeftRight`].
+pub fn left_right<T: Absorb<O> + Clone, O>(init_val: T) -> (LeftRight<T, O>, Reader<T>) {
+    let w = LeftRight::new(init_val);
+    let r = w.reader();
+    (w, r)
+}
+
 /// Writer for a slot. Can also read the value, and create more readers
 ///
-/// Only 1 of these per slot exists. If multiple writers are needed, wrap this
-/// in a mutex.
+/// Only 1 of these per slot exists. If multiple writers are needed, convert
+/// this into a [`SharedWriter`] with [`Writer::into_shared()`].
 pub struct Writer<T> {
     shared: Arc<Shared<T>>,
+    /// If set, old values are passed to this instead of being returned.
+    retire: Option<RetireFn<T>>,
+    /// If set, old values are handed to a background thread instead of being collected here.
+    reclaimer: Option<reclaimer::Background<T>>,
+    /// Decides when `write()` scans `prevs`.
+    policy: Box<dyn ReclaimPolicy + Send>,
+    /// Cap on `prevs` for `try_write()` & `write_bounded()`.
+    limit: Option<limit::Limit<T>>,
+    /// Buffer for `try_sync_drain()`, empty between calls.
+    drained: Vec<Box<T>>,
+}
+
+/// Receives old values once no reader can be using them
+///
+/// Installed with [`Builder::retire()`]. When a `Writer` has a `Retire`, old values are passed to
+/// it by `try_sync()`, `write()` & `sync()` (and similar) instead of being returned, so no `Vec`
+/// is allocated for them. This can be used to run custom destructors, pool values, or send them
+/// to another thread.
+///
+/// Implemented for any `FnMut(Box<T>)`.
+pub trait Retire<T> {
+    /// Called once for each old value
+    fn retire(&mut self, val: Box<T>);
+}
+
+impl<T, F: FnMut(Box<T>)> Retire<T> for F {
+    fn retire(&mut self, val: Box<T>) {
+        self(val)
+    }
+}
+
+type RetireFn<T> = Box<dyn Retire<T> + Send>;
+
+/// Pass old values to `retire` if set, otherwise push them into `v`
+fn retire_or_push<'a, T>(
+    retire: &'a mut Option<RetireFn<T>>,
+    v: &'a mut Vec<Box<T>>,
+) -> impl FnMut(Box<T>) + 'a {
+    move |val| match retire {
+        Some(retire) => retire.retire(val),
+        None => v.push(val),
+    }
 }
 
 // If `T` is not Sync, we can't allow Writer (or Reader) to be sent to another thread, as `Writer`
@@ -52,26 +198,18 @@ unsafe impl<T: Send + Sync> Sync for Writer<T> {}
 struct Shared<T> {
     /// Value that readers are expected to read at this time.
     ///
-    /// Is really a `Box<T>`, we need `AtomicPtr` so we can load/store it.
-    active: atomic::AtomicPtr<T>,
+    /// Is really a `Box<Node<T>>`, we need `AtomicPtr` so we can load/store it.
+    active: atomic::AtomicPtr<Node<T>>,
 
-    /// An array of epochs, one per reader.
+    /// An array of epochs, one per reader, shared with the reclaimer thread (if any).
     ///
-    /// references to each reader's epoch exist:
-    ///  - one here
-    ///  - one in the `Reader` itself
-    ///  - zero or more in `prevs`
-    ///
-    /// Readers track their index in the slab and use it to remove themselves when they're dropped.
-    ///
-    /// This is locked when a new reader is created, and when a writer is writing a new value.
-    /// Contention is limited as long as we don't create readers too often and/or don't write new
-    /// values too often.
-    epochs: Mutex<slab::Slab<Arc<atomic::AtomicUsize>>>,
+    /// Readers track the index of their slot and use it to release the slot when they're dropped.
+    /// `prevs` refers to slots by index & generation.
+    epochs: Arc<Epochs>,
 
-    /// Previous active values along with a vec of readers, each with a snapshot of the epoch at
-    /// the time _after_ the previous active value was made inactive and a reference to the
-    /// reader's epoch counter so we can determine what epoch that reader is at now.
+    /// Previous active values, grouped into batches that share a vec of readers, each with a
+    /// snapshot of the epoch at the time _after_ the previous active value was made inactive and
+    /// the index of the reader's epoch slot so we can determine what epoch that reader is at now.
     // Conceptually, this is a field in `Writer`. We place it in `Shared` to avoid having Writer
     // dropping spin. Because `Shared` is in an `Arc`, by the time drop occurs all `Reader`s will
     // have released their `ReadGuard`s, and we can safely drop the `Vec`.
@@ -79,8 +217,274 @@ struct Shared<T> {
     // Modifying the content of the `Box<T>` is not permitted until all readers have dropped their
     // references to the `Box<T>`. This is enforced by the `ReadGuard`'s lifetime & the epoch
     // count.
-    #[allow(clippy::type_complexity)]
-    prevs: UnsafeCell<Vec<(Box<T>, Vec<(usize, Arc<atomic::AtomicUsize>)>)>>,
+    prevs: UnsafeCell<Prevs<T>>,
+
+    /// Version of the most recently published value. Stored after `active` is updated, so it may
+    /// briefly lag behind the version of the node in `active`.
+    ///
+    /// Readers compare this against the version they last read to determine if they've missed any
+    /// writes.
+    version: atomic::AtomicU64,
+
+    /// Set when the `Writer` is dropped. No more values will be published after this is set.
+    closed: atomic::AtomicBool,
+
+    /// Wakers for `Changed` futures that are waiting for a new value.
+    ///
+    /// Each `Changed` future owns at most 1 entry, and removes it when dropped.
+    wakers: Mutex<slab::Slab<Waker>>,
+
+    /// Number of entries in `wakers`. Allows the writer to skip locking `wakers` when nobody is
+    /// waiting.
+    waiting: atomic::AtomicUsize,
+
+    /// Set when `wakers` need to be woken. The writer never blocks on `wakers`: if it's held, the
+    /// holder wakes them after releasing it (see `Shared::wake_pending()`).
+    notify_pending: atomic::AtomicBool,
+
+    /// Set while the writer is parked in `Writer::sync()` (or similar) waiting for readers to
+    /// release old values. Readers check this when dropping a `ReadGuard`, and unpark
+    /// `sync_waiter` if it is set.
+    ///
+    /// Readers don't fence between storing their epoch and loading this, so they may miss it. The
+    /// writer never parks without a timeout, see `SYNC_PARK_MAX`.
+    sync_waiting: atomic::AtomicBool,
+
+    /// The thread to unpark when `sync_waiting` is set. Readers only `try_lock()` this.
+    sync_waiter: Mutex<Option<thread::Thread>>,
+
+    /// Optional instrumentation, shared with the reclaimer thread (if any).
+    monitor: Arc<Monitor>,
+
+    /// Fences used between readers' epochs and the writer, see `Builder::membarrier()`.
+    fences: membarrier::Fences,
+}
+
+/// Optional instrumentation of a slot, configured with the `Builder`
+#[derive(Default)]
+struct Monitor {
+    /// Reports old values that stay in use for too long.
+    stall: Option<StallDetector>,
+    metrics: Option<metrics::Metrics>,
+    observer: Option<Box<dyn RcuObserver + Send + Sync>>,
+    observations: Option<latency::Observations>,
+}
+
+impl Monitor {
+    /// Timestamp for a value being replaced, if anything needs one
+    fn retired_at(&self) -> Option<Instant> {
+        if self.stall.is_some() || self.metrics.is_some() {
+            Some(Instant::now())
+        } else {
+            None
+        }
+    }
+}
+
+/// A value that has been replaced, and may still be in use by the readers of its `prevs::Batch`
+struct Retired<T> {
+    node: Box<Node<T>>,
+    /// When `node` was replaced, only tracked when there is a `StallDetector` or metrics.
+    retired_at: Option<Instant>,
+    /// When this was last reported by the `StallDetector`.
+    last_report: Option<Instant>,
+}
+
+/// A published value along with the metadata that identifies it
+struct Node<T> {
+    value: Box<T>,
+    version: u64,
+    published_at: Instant,
+}
+
+impl<T> Node<T> {
+    /// Move the value out of `node`, keeping the node's allocation (see `Prevs::node()`)
+    fn into_value(node: Box<Node<T>>) -> (Box<T>, Box<MaybeUninit<Node<T>>>) {
+        let node = Box::into_raw(node);
+        // SAFETY: `node` is valid, and the remaining fields don't need to be dropped. We give up
+        // the allocation as uninitialized, so `value` is never dropped there.
+        unsafe {
+            let value = ptr::read(&(*node).value);
+            (value, Box::from_raw(node.cast::<MaybeUninit<Node<T>>>()))
+        }
+    }
+}
+
+impl<T> Shared<T> {
+    /// Has a version newer than `seen` been published?
+    fn has_changed(&self, seen: u64) -> bool {
+        self.version.load(atomic::Ordering::Acquire) > seen
+    }
+
+    /// Wake all `Changed` futures
+    fn notify(&self) {
+        // Pairs with the fence in `Changed::poll()`: either we see the waiter, or the waiter sees
+        // the new `version`/`closed`.
+        atomic::fence(atomic::Ordering::SeqCst);
+        if self.waiting.load(atomic::Ordering::Relaxed) == 0 {
+            return;
+        }
+
+        self.notify_pending.store(true, atomic::Ordering::Relaxed);
+        self.wake_pending();
+    }
+
+    /// Wake all `Changed` futures if `notify_pending` is set
+    ///
+    /// Never blocks: if `wakers` is held by another thread, that thread does it instead. Anyone
+    /// releasing `wakers` must call this.
+    fn wake_pending(&self) {
+        loop {
+            // Pairs with the fence here in the thread holding `wakers`: either we get the lock, or
+            // they see `notify_pending` after releasing it.
+            atomic::fence(atomic::Ordering::SeqCst);
+            if !self.notify_pending.load(atomic::Ordering::Relaxed) {
+                return;
+            }
+            let wakers = match self.wakers.try_lock() {
+                Ok(wakers) => wakers,
+                Err(TryLockError::WouldBlock) => return,
+                Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            };
+            if self.notify_pending.swap(false, atomic::Ordering::Relaxed) {
+                for (_, waker) in wakers.iter() {
+                    waker.wake_by_ref();
+                }
+            }
+            // A writer may have set `notify_pending` and failed to get `wakers` while we held it,
+            // check again now that we've released it.
+            drop(wakers);
+        }
+    }
+
+    /// Unpark the writer if it is waiting in `Writer::sync()`
+    ///
+    /// Best effort: this never blocks, and may miss a writer that just started waiting.
+    fn wake_sync_waiter(&self) {
+        if !self.sync_waiting.load(atomic::Ordering::Relaxed) {
+            return;
+        }
+
+        // If another thread holds `sync_waiter`, it's either a reader doing this same unpark or
+        // the writer, which hasn't parked yet.
+        let waiter = match self.sync_waiter.try_lock() {
+            Ok(waiter) => waiter,
+            Err(TryLockError::WouldBlock) => return,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        };
+        if let Some(t) = waiter.as_ref() {
+            t.unpark();
+        }
+    }
+
+    /// Remove the values in `prevs` that are no longer in use by any reader and pass them to
+    /// `retire`, then report any remaining values that are overdue to the `StallDetector`
+    fn try_sync(
+        prevs: &mut Prevs<T>,
+        epochs: &Epochs,
+        monitor: &Monitor,
+        mut retire: impl FnMut(Box<T>),
+    ) {
+        #[cfg(feature = "tracing")]
+        let scanned = prevs.len();
+        prevs.collect(epochs, |value, version, retired_at| {
+            if let Some(metrics) = &monitor.metrics {
+                metrics.reclaimed(retired_at);
+            }
+            if let Some(observer) = &monitor.observer {
+                observer.reclaimed(version);
+            }
+            retire(value);
+        });
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            reclaimed = scanned - prevs.len(),
+            pending = prevs.len(),
+            holdouts = holdouts(prevs),
+            "scanned for old values"
+        );
+
+        if let Some(metrics) = &monitor.metrics {
+            metrics.scanned();
+        }
+        if let Some(stall) = &monitor.stall {
+            stall.check(prevs, epochs);
+        }
+    }
+
+    /// Repeatedly remove values in `prevs` that are no longer in use (passing them to `retire`),
+    /// parking between scans until either `done(prevs)` (usually `prevs.is_empty()`) or
+    /// `deadline` passes
+    ///
+    /// Only 1 thread may call this at a time.
+    fn sync_until(
+        &self,
+        prevs: &mut Prevs<T>,
+        deadline: Option<Instant>,
+        mut retire: impl FnMut(Box<T>),
+        mut done: impl FnMut(&Prevs<T>) -> bool,
+    ) -> Result<(), Outstanding> {
+        let monitor = &*self.monitor;
+        Self::try_sync(prevs, &self.epochs, monitor, &mut retire);
+        if done(prevs) {
+            return Ok(());
+        }
+        // Don't bother readers with waking us (or scan again) if we won't wait, as for
+        // `try_write()`.
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Err(Outstanding::of(prevs));
+        }
+
+        *self.sync_waiter.lock().unwrap() = Some(thread::current());
+        self.sync_waiting.store(true, atomic::Ordering::Relaxed);
+
+        // Readers may miss `sync_waiting`, so we only park for a bit before scanning again. Each
+        // time we wake up to find nothing collectable, park for longer.
+        let mut park_for = SYNC_PARK_MIN;
+        let timed_out = loop {
+            Self::try_sync(prevs, &self.epochs, monitor, &mut retire);
+            if done(prevs) {
+                break false;
+            }
+
+            // Wake up at least once per threshold so the stall detector gets to check.
+            let mut timeout = park_for;
+            if let Some(stall) = &monitor.stall {
+                timeout = timeout.min(stall.threshold);
+            }
+            if let Some(deadline) = deadline {
+                let now = Instant::now();
+                if now >= deadline {
+                    break true;
+                }
+                timeout = timeout.min(deadline - now);
+            }
+            park_timeout(timeout);
+            park_for = (park_for * 2).min(SYNC_PARK_MAX);
+        };
+
+        self.sync_waiting.store(false, atomic::Ordering::Relaxed);
+        *self.sync_waiter.lock().unwrap() = None;
+
+        if timed_out {
+            Err(Outstanding::of(prevs))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Number of distinct readers still using the values in `prevs`, as of the last scan
+fn holdouts<T>(prevs: &Prevs<T>) -> usize {
+    let mut readers: Vec<usize> = prevs
+        .batches()
+        .iter()
+        .flat_map(|batch| batch.readers.iter().map(|holdout| holdout.index))
+        .collect();
+    readers.sort_unstable();
+    readers.dedup();
+    readers.len()
 }
 
 impl<T> Drop for Shared<T> {
@@ -93,32 +497,30 @@ impl<T> Drop for Shared<T> {
 }
 
 impl<T> Writer<T> {
-    fn prevs(&self) -> &Vec<(Box<T>, Vec<(usize, Arc<atomic::AtomicUsize>)>)> {
+    fn prevs(&self) -> &Prevs<T> {
         // SAFETY: only this `Writer` can access `prevs`.
         unsafe { &*self.shared.prevs.get() }
     }
 
-    fn prevs_mut(&mut self) -> &mut Vec<(Box<T>, Vec<(usize, Arc<atomic::AtomicUsize>)>)> {
+    fn prevs_mut(&mut self) -> &mut Prevs<T> {
         // SAFETY: only this `Writer` can access `prevs`.
         unsafe { &mut *self.shared.prevs.get() }
     }
 
     /// Create a new `Writer` with an initial value
     ///
-    /// The `Writer` can than be used to obtain one or more [`Reader`]s.
+    /// The `Writer` can than be used to obtain one or more [`Reader`]s. Use [`Builder`] to
+    /// configure the `Writer`.
     pub fn new(init_val: Box<T>) -> Writer<T> {
-        let shared = Arc::new(Shared {
-            active: atomic::AtomicPtr::new(Box::into_raw(init_val)),
-            epochs: Mutex::new(slab::Slab::new()),
-            prevs: UnsafeCell::new(Vec::new()),
-        });
-
-        Writer { shared }
+        Builder::new().build(init_val)
     }
 
     /// Obtain a reader for the value stored by this writer
+    ///
+    /// The current value is considered already seen by the new reader, see [`Reader::changed()`].
     pub fn reader(&self) -> Reader<T> {
-        Reader::<T>::new(self.shared.clone())
+        let seen = self.shared.version.load(atomic::Ordering::Relaxed);
+        Reader::<T>::new(self.shared.clone(), seen)
     }
 
     /// Write a new value, returning any old values that are no longer in use
@@ -126,15 +528,79 @@ impl<T> Writer<T> {
     /// You may get none of the old values back as readers may still exist. The next time you write
     /// (or call `try_sync()`), additional previous values are returned. Old values may be returned
     /// in any order.
+    ///
+    /// Whether old values are scanned for at all is decided by the [`ReclaimPolicy`], by default
+    /// every write scans.
     pub fn write(&mut self, val: Box<T>) -> Vec<Box<T>> {
-        // scan `self.prev` for things we can discard and discard them.
-        let mut r = self.try_sync();
+        let mut v = Vec::new();
+        self.write_into(val, &mut v);
+        v
+    }
+
+    /// Like `write()`, but old values that are no longer in use are appended to `v`
+    ///
+    /// Reusing `v` across writes (and clearing it in between) avoids allocating a `Vec` for the
+    /// old values on each write.
+    pub fn write_into(&mut self, val: Box<T>, v: &mut Vec<Box<T>>) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("write", version = self.version() + 1).entered();
 
         self.write_nosync(val);
 
-        r.extend(self.try_sync());
+        // scan `self.prev` for things we can discard and discard them.
+        if self.reclaimer.is_none() && self.policy.should_scan(self.prevs().len()) {
+            self.try_sync_into(v);
+        }
+    }
 
-        r
+    /// Write a new value produced by updating a copy of the current value in place with `f`,
+    /// returning any other old values that are no longer in use
+    ///
+    /// If an old value is no longer in use by any reader, its `Box` is reused for the new value:
+    /// it is updated to match the current value with `Clone::clone_from()` before `f` is called.
+    /// Otherwise the current value is cloned into a new `Box`. Once old values are being
+    /// collected, each write reuses the value replaced by an earlier write, so no new `T` is
+    /// allocated (and `clone_from()` can reuse any allocations within `T`).
+    ///
+    /// Old values are only reused when they are collected by this call, with a [`Reclaimer`] the
+    /// current value is always cloned. To find one, this always scans for old values before
+    /// writing, regardless of the [`ReclaimPolicy`].
+    pub fn write_with<F: FnOnce(&mut T)>(&mut self, f: F) -> Vec<Box<T>>
+    where
+        T: Clone,
+    {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("write_with", version = self.version() + 1).entered();
+
+        let mut spare = None;
+        let mut v = Vec::new();
+        if self.reclaimer.is_none() {
+            // SAFETY: only this `Writer` can access `prevs`.
+            let prevs = unsafe { &mut *self.shared.prevs.get() };
+            let mut retire = retire_or_push(&mut self.retire, &mut v);
+            Shared::try_sync(
+                prevs,
+                &self.shared.epochs,
+                &self.shared.monitor,
+                |val| match spare {
+                    None => spare = Some(val),
+                    Some(_) => retire(val),
+                },
+            );
+        }
+
+        let mut val = match spare {
+            Some(mut val) => {
+                T::clone_from(&mut val, self.read());
+                val
+            }
+            None => Box::new(self.read().clone()),
+        };
+        f(&mut val);
+
+        self.write_nosync(val);
+
+        v
     }
 
     /// Read the current value in this writer.
@@ -148,7 +614,22 @@ impl<T> Writer<T> {
         // because we've bound its lifetime to `&self`.
         // There are no mutable references, because we only hand out read-only
         // refs to the readers.
-        unsafe { &*self.shared.active.load(atomic::Ordering::Relaxed) }
+        unsafe { &(*self.shared.active.load(atomic::Ordering::Relaxed)).value }
+    }
+
+    /// Are readers relying on `membarrier()` instead of a full fence per read?
+    ///
+    /// `true` if enabled with [`Builder::membarrier()`] and supported by the system.
+    pub fn uses_membarrier(&self) -> bool {
+        self.shared.fences == membarrier::Fences::Asymmetric
+    }
+
+    /// Version of the current value in this writer
+    ///
+    /// The initial value has version `0`, and each write increments the version by 1.
+    pub fn version(&self) -> u64 {
+        // Only we update `version`.
+        self.shared.version.load(atomic::Ordering::Relaxed)
     }
 
     /// Are there any old values waiting to be collected?
@@ -156,7 +637,10 @@ impl<T> Writer<T> {
     /// These may or may not still have readers that are still using them. If the readers for a
     /// particular value have moved on, those old values will be returned by `try_sync()`.
     pub fn has_old_values(&self) -> bool {
-        !self.prevs().is_empty()
+        match &self.reclaimer {
+            Some(reclaimer) => reclaimer.handle().has_old_values(),
+            None => !self.prevs().is_empty(),
+        }
     }
 
     /// Check if we can release previous values and return them
@@ -166,54 +650,178 @@ impl<T> Writer<T> {
     /// If you want to wait for all readers to finish proactively, schedule work using a timer to
     /// call this periodically. This is generally not required unless you need to obtain old values
     /// for some special purpose.
+    ///
+    /// With a [`Reclaimer`], this only asks the reclaimer thread to scan and returns nothing.
     pub fn try_sync(&mut self) -> Vec<Box<T>> {
         let mut v = Vec::new();
+        self.try_sync_into(&mut v);
+        v
+    }
 
-        // We need to move `val` out of `prevs` and into `v`. `extract_if` would work.
-        // `retain_mut` doesn't unless we play some unsafe games with pointers in `prev`.
-        //
-        // FIXME: switch to `extract_if` once it's stable.
-
-        let mut i = 0;
-        while i < self.prevs().len() {
-            let epochs = &mut self.prevs_mut()[i].1;
-            epochs.retain(|(prev, epoch)| {
-                let new = epoch.load(atomic::Ordering::Relaxed);
-                new == *prev
-            });
-
-            if epochs.is_empty() {
-                // TODO: consider if we require a fence here to ensure all reads
-                // have occured before this point.
+    /// Like `try_sync()`, but old values that are no longer in use are appended to `v`
+    ///
+    /// Values are appended oldest first. Reusing `v` avoids allocating a `Vec` for each scan.
+    pub fn try_sync_into(&mut self, v: &mut Vec<Box<T>>) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("try_sync").entered();
 
-                // SAFETY: no readers are left (because all have moved to a new
-                // epoch). We're removing it from `self.prevs` too, so there
-                // won't be another `Box` created for this pointer.
-                v.push(self.prevs_mut().remove(i).0);
-            } else {
-                i += 1;
-            }
+        if let Some(reclaimer) = &self.reclaimer {
+            reclaimer.handle().wake();
+            return;
         }
 
-        v
+        // SAFETY: only this `Writer` can access `prevs`.
+        let prevs = unsafe { &mut *self.shared.prevs.get() };
+        Shared::try_sync(
+            prevs,
+            &self.shared.epochs,
+            &self.shared.monitor,
+            retire_or_push(&mut self.retire, v),
+        );
+    }
+
+    /// Like `try_sync()`, but returns an iterator over the old values that are no longer in use
+    ///
+    /// The values are kept in a buffer owned by this `Writer`, which is reused by later calls so
+    /// no allocation is needed once it is large enough. Values that are not taken from the
+    /// iterator are dropped along with it.
+    pub fn try_sync_drain(&mut self) -> Drain<'_, T> {
+        let mut drained = std::mem::take(&mut self.drained);
+        self.try_sync_into(&mut drained);
+        self.drained = drained;
+        Drain {
+            inner: self.drained.drain(..),
+        }
     }
 
     /// `try_sync()` repeatedly until all old values are collected
     ///
-    /// This spins, and in general should be avoided.
+    /// Between scans, the current thread is parked. Readers unpark it when they drop a
+    /// `ReadGuard`, and it also wakes up periodically (at most every 10ms) in case a reader
+    /// didn't see it was waiting. This blocks for as long as any reader holds a `ReadGuard` to an
+    /// old value, see `sync_timeout()` & `sync_deadline()` to bound the wait.
     pub fn sync(&mut self) -> Vec<Box<T>> {
-        let mut r = Vec::new();
-
-        while !self.prevs().is_empty() {
-            let v = self.try_sync();
-            if v.is_empty() {
-                // TODO: consider if there's a better way to choose how to yield
-                thread::yield_now();
-            } else {
-                r.extend(v);
+        match self.sync_until(None) {
+            Ok(v) => v,
+            Err(e) => e.reclaimed,
+        }
+    }
+
+    /// Like `sync()`, but give up after `timeout` has elapsed
+    pub fn sync_timeout(&mut self, timeout: Duration) -> Result<Vec<Box<T>>, SyncTimeout<T>> {
+        // If the deadline isn't representable, it's far enough away to be treated as no deadline.
+        self.sync_until(Instant::now().checked_add(timeout))
+    }
+
+    /// Like `sync()`, but give up once `deadline` has passed
+    ///
+    /// If the deadline passes while some old values are still in use by readers, the values that
+    /// were collected are returned in the error along with a count of what is still outstanding.
+    pub fn sync_deadline(&mut self, deadline: Instant) -> Result<Vec<Box<T>>, SyncTimeout<T>> {
+        self.sync_until(Some(deadline))
+    }
+
+    fn sync_until(&mut self, deadline: Option<Instant>) -> Result<Vec<Box<T>>, SyncTimeout<T>> {
+        #[cfg(feature = "tracing")]
+        let _span = {
+            let timeout = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+            tracing::debug_span!("sync", ?timeout).entered()
+        };
+
+        let mut v = Vec::new();
+        let r = self.collect_until(deadline, &mut v, |prevs| prevs.is_empty());
+        #[cfg(feature = "tracing")]
+        match &r {
+            Ok(()) => tracing::debug!(returned = v.len(), "synced"),
+            Err(o) => tracing::debug!(
+                returned = v.len(),
+                pending = o.pending,
+                holdouts = o.readers,
+                "sync timed out"
+            ),
+        }
+        match r {
+            Ok(()) => Ok(v),
+            Err(o) => Err(o.into_timeout(v)),
+        }
+    }
+
+    /// Collect old values (into `v` if there's no `Retire`) until `done` returns `true` for the
+    /// remaining ones, or `deadline` passes
+    fn collect_until(
+        &mut self,
+        deadline: Option<Instant>,
+        v: &mut Vec<Box<T>>,
+        done: impl FnMut(&Prevs<T>) -> bool,
+    ) -> Result<(), Outstanding> {
+        match &self.reclaimer {
+            Some(reclaimer) => reclaimer
+                .handle()
+                .sync_until(&self.shared, deadline, v, done),
+            None => {
+                // SAFETY: only this `Writer` can access `prevs`.
+                let prevs = unsafe { &mut *self.shared.prevs.get() };
+                self.shared
+                    .sync_until(prevs, deadline, retire_or_push(&mut self.retire, v), done)
+            }
+        }
+    }
+
+    /// Write a new value unless the limit on old values waiting to be collected would be exceeded
+    ///
+    /// The limit is set with [`Builder::max_pending()`] or [`Builder::max_pending_weight()`]. If
+    /// replacing the current value would exceed it, old values are scanned for once. If that
+    /// doesn't make enough room, `val` is returned in the error along with any old values that
+    /// were collected. Without a limit, this is the same as `write()`.
+    pub fn try_write(&mut self, val: Box<T>) -> Result<Vec<Box<T>>, TryWriteError<T>> {
+        let mut v = Vec::new();
+        if let Some(pending) = self.make_room(Some(Instant::now()), &mut v) {
+            return Err(TryWriteError {
+                value: val,
+                reclaimed: v,
+                pending,
+            });
+        }
+
+        v.extend(self.write(val));
+        Ok(v)
+    }
+
+    /// Write a new value, first waiting until the limit on old values waiting to be collected
+    /// allows it
+    ///
+    /// Like `try_write()`, but instead of failing, the current thread is parked until readers
+    /// release enough old values (see `sync()`). Without a limit, this is the same as `write()`.
+    pub fn write_bounded(&mut self, val: Box<T>) -> Vec<Box<T>> {
+        let mut v = Vec::new();
+        self.make_room(None, &mut v);
+        v.extend(self.write(val));
+        v
+    }
+
+    /// Collect old values until the current value can be replaced without exceeding `limit`, or
+    /// `deadline` passes. Returns the pending weight if there still isn't room.
+    fn make_room(&mut self, deadline: Option<Instant>, v: &mut Vec<Box<T>>) -> Option<usize> {
+        let limit = self.limit.take()?;
+        let weight = limit.weigh(self.read());
+        let pending = match &self.reclaimer {
+            Some(reclaimer) => reclaimer.handle().pending(|prevs| limit.pending(prevs)),
+            None => limit.pending(self.prevs()),
+        };
+
+        let mut r = None;
+        if !limit.has_room(pending, weight) {
+            let mut pending = pending;
+            let done = |prevs: &Prevs<T>| {
+                pending = limit.pending(prevs);
+                limit.has_room(pending, weight)
+            };
+            if self.collect_until(deadline, v, done).is_err() {
+                r = Some(pending);
             }
         }
 
+        self.limit = Some(limit);
         r
     }
 
@@ -221,112 +829,457 @@ impl<T> Writer<T> {
     ///
     /// If you use this, calling `try_sync()` is required to avoid leaking old values. In general,
     /// `Writer::write()` is a better choice.
+    ///
+    /// Readers waiting in [`Reader::changed()`] are woken after the value is published. With a
+    /// [`Reclaimer`], the old value is handed to the reclaimer thread and no `try_sync()` is
+    /// needed.
+    ///
+    /// This is wait free: readers are recorded with a single pass over their slots, and waking
+    /// readers never waits for a lock held by a reader. Once old values are being collected (by
+    /// `try_sync()` or similar), the memory used to track them is reused, so the only allocations
+    /// are for the values themselves. With a `Reclaimer`, handing off the old value takes a lock
+    /// that is only shared with the reclaimer thread, and allocates.
     pub fn write_nosync(&mut self, val: Box<T>) {
+        // SAFETY: only this `Writer` can access `prevs`.
+        let prevs = unsafe { &mut *self.shared.prevs.get() };
+
         // We're the only writer, so `Relaxed` is fine. We avoid a `swap`
         // because that provides extra garuntees we don't need.
         let prev = self.shared.active.load(atomic::Ordering::Relaxed);
+        let version = self.version() + 1;
+        let node = prevs.node(Node {
+            value: val,
+            version,
+            published_at: Instant::now(),
+        });
+        if let Some(observations) = &self.shared.monitor.observations {
+            observations.published(version, node.published_at);
+        }
 
         // Half of a Release-Acquire pair, see `Reader::read()` for the `Acquire` half. `Release`
         // ensures that `val` is fully initialized before it is exposed to other threads.
         self.shared
             .active
-            .store(Box::into_raw(val), atomic::Ordering::Release);
-        // Can be `Release` if the `SeqCst` fence is placed before the epoch
-        // iter below (after epochs.lock())
-        atomic::fence(atomic::Ordering::SeqCst);
+            .store(Box::into_raw(node), atomic::Ordering::Release);
+        // Pairs with the fence in `Reader::lock()`: either the reader sees the new `active`, or
+        // we see its odd epoch in the scan below.
+        self.shared.fences.heavy();
 
-        // add `prev` to `self.prevs`, collect initial remaining readers, and see if we can retire
-        // it.
+        let monitor = &self.shared.monitor;
+        if let Some(observer) = &monitor.observer {
+            observer.published(version);
+        }
 
-        // NOTE: see if we can predict an initial slab size better than 0
+        // add `prev` to `self.prevs` along with the initial remaining readers.
         //
-        // NOTE: `Vec` gives us `retain_mut` for collecting these at the end. If
-        // `Slab` had retain_mut we could use it instead if it provides better perf.
-        let mut remaining_readers = Vec::new();
-
-        // initial scan, locks epochs
-        {
-            let epochs = self.shared.epochs.lock().unwrap();
-            // FIXME: the `epochs.lock()` should already be doing this. Check `loom`.
-            // FIXME: determine why anything less than `SeqCst` here causes loom to fail.
-            for (_, epoch) in epochs.iter() {
-                // This pairs with a `Release` in `Reader::read()`, which ensures all the
-                // writes/reads by the reader are retired. We don't need to see the writes done
-                // by the caller of `Reader::read()`, so `Relaxed` is sufficient (`Acquire` would
-                // ensure we see writes).
-                let v = epoch.load(atomic::Ordering::Relaxed);
-                if v & 1 != 0 {
-                    remaining_readers.push((v, epoch.clone()));
-                }
-            }
+        // initial scan, lock free: readers may be registering or dropping concurrently.
+        let holdouts = prevs.push(
+            Retired {
+                node: unsafe { Box::from_raw(prev) },
+                retired_at: monitor.retired_at(),
+                last_report: None,
+            },
+            &self.shared.epochs,
+        );
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(version, holdouts, "published");
+
+        if let Some(metrics) = &monitor.metrics {
+            metrics.wrote();
+        }
+        if let Some(observer) = &monitor.observer {
+            observer.retired(version - 1, holdouts);
+        }
+        if let Some(reclaimer) = &self.reclaimer {
+            reclaimer.handle().retire(prevs);
+        }
+
+        // `Release` ensures readers that see the new version also see the new value in `active`.
+        self.shared
+            .version
+            .store(version, atomic::Ordering::Release);
+        self.shared.notify();
+    }
+}
+
+impl<T> Drop for Writer<T> {
+    fn drop(&mut self) {
+        if let Some(reclaimer) = self.reclaimer.take() {
+            // Values still in use are left in `Shared`, like any others we haven't collected.
+            reclaimer.shutdown(self.prevs_mut());
+        }
+
+        // Wake any readers waiting on a new value, they won't be getting one.
+        self.shared.closed.store(true, atomic::Ordering::Relaxed);
+        self.shared.notify();
+    }
+}
+
+/// Shortest time `Shared::sync_until()` parks for between scans.
+const SYNC_PARK_MIN: Duration = Duration::from_micros(100);
+
+/// Longest time `Shared::sync_until()` parks for between scans, which bounds how long `sync()`
+/// keeps waiting after the last old value is released if the reader releasing it doesn't see
+/// that we're waiting.
+const SYNC_PARK_MAX: Duration = Duration::from_millis(10);
+
+#[cfg(not(loom))]
+fn park_timeout(timeout: Duration) {
+    thread::park_timeout(timeout)
+}
+
+// loom doesn't model time, treat the timeout as a hint to let other threads run.
+#[cfg(loom)]
+fn park_timeout(_timeout: Duration) {
+    thread::yield_now()
+}
+
+/// Old values that were still in use when a `sync_deadline()` passed
+struct Outstanding {
+    pending: usize,
+    readers: usize,
+}
+
+impl Outstanding {
+    /// The values left in `prevs`
+    fn of<T>(prevs: &Prevs<T>) -> Outstanding {
+        Outstanding {
+            pending: prevs.len(),
+            readers: holdouts(prevs),
         }
+    }
+
+    fn into_timeout<T>(self, reclaimed: Vec<Box<T>>) -> SyncTimeout<T> {
+        SyncTimeout {
+            reclaimed,
+            pending: self.pending,
+            readers: self.readers,
+        }
+    }
+}
+
+/// Iterator over old values that are no longer in use, returned by [`Writer::try_sync_drain()`]
+///
+/// Values are yielded oldest first. Any that remain when this is dropped are dropped too.
+pub struct Drain<'a, T> {
+    inner: std::vec::Drain<'a, Box<T>>,
+}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = Box<T>;
+
+    fn next(&mut self) -> Option<Box<T>> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Drain<'a, T> {
+    fn next_back(&mut self) -> Option<Box<T>> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Drain<'a, T> {}
+
+impl<'a, T> fmt::Debug for Drain<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Drain")
+            .field("remaining", &self.inner.len())
+            .finish()
+    }
+}
+
+/// Error returned by [`Writer::sync_timeout()`] & [`Writer::sync_deadline()`] when old values are
+/// still in use by readers after the deadline
+pub struct SyncTimeout<T> {
+    /// Old values that were collected before the deadline passed
+    ///
+    /// Always empty if the `Writer` has a [`Retire`].
+    pub reclaimed: Vec<Box<T>>,
+    /// Number of old values still in use by readers
+    pub pending: usize,
+    /// Number of readers that are still using old values
+    pub readers: usize,
+}
+
+impl<T> fmt::Debug for SyncTimeout<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyncTimeout")
+            .field("reclaimed", &self.reclaimed.len())
+            .field("pending", &self.pending)
+            .field("readers", &self.readers)
+            .finish()
+    }
+}
+
+impl<T> fmt::Display for SyncTimeout<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "timed out with {} old values still in use by {} readers",
+            self.pending, self.readers
+        )
+    }
+}
+
+impl<T> std::error::Error for SyncTimeout<T> {}
+
+/// Error returned by [`Writer::try_write()`] when writing would exceed the limit on old values
+/// waiting to be collected
+pub struct TryWriteError<T> {
+    /// The value that was not written
+    pub value: Box<T>,
+    /// Old values that were collected while trying to make room
+    ///
+    /// Always empty if the `Writer` has a [`Retire`].
+    pub reclaimed: Vec<Box<T>>,
+    /// Weight of the old values still in use by readers (their number, unless a [`Weigher`] is
+    /// used)
+    pub pending: usize,
+}
+
+impl<T> fmt::Debug for TryWriteError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TryWriteError")
+            .field("reclaimed", &self.reclaimed.len())
+            .field("pending", &self.pending)
+            .finish_non_exhaustive()
+    }
+}
 
-        self.prevs_mut()
-            .push((unsafe { Box::from_raw(prev) }, remaining_readers));
+impl<T> fmt::Display for TryWriteError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "limit on old values reached with {} pending",
+            self.pending
+        )
     }
 }
 
+impl<T> std::error::Error for TryWriteError<T> {}
+
 /// Something which can read the value, use `[Writer::reader]` to get one, or clone an existing `Reader`
+///
+/// A `Reader` can be sent to another thread, but not shared between threads: `read(&self)` tracks
+/// nested read sections in plain (non-atomic) fields, and enters the outermost one with a load &
+/// store of the epoch rather than a read-modify-write, which is only sound from a single thread.
+/// Clone it to read from multiple threads. `Reader` was `Sync` before 2.0, when `read()` took
+/// `&mut self`.
+///
+/// ```compile_fail
+/// // `Reader` is not `Sync`, see above.
+/// fn is_sync<T: Sync>() {}
+/// is_sync::<local_rcu::Reader<usize>>();
+/// ```
+///
+/// As a result, a [`ReadGuard`] (which borrows its `Reader`) can't be sent to another thread
+/// either, so it can't be held across an `.await` in a task that may move between threads. Use
+/// [`Reader::read_owned()`] there instead. [`Reader::changed()`] doesn't borrow the `Reader` this
+/// way and can be awaited anywhere.
 pub struct Reader<T> {
     shared: Arc<Shared<T>>,
-    epoch: Arc<atomic::AtomicUsize>,
+    /// Our slot in `Shared::epochs`, which `shared` keeps alive.
+    epoch: *const Epoch,
     epoch_index: usize,
-    // pointer used so we get !Send/!Sync without the `unsafe impl`s below.
+    /// Number of read sections (`ReadGuard`s and similar) currently held.
+    ///
+    /// `epoch` is only updated when entering the outermost read section and leaving the last one.
+    /// Nested read sections don't need to touch `epoch`: the writer won't reclaim anything we
+    /// load until the outermost read section ends.
+    depth: Cell<usize>,
+    /// When the outermost read section was entered, only tracked when metrics are enabled.
+    held_since: Cell<Option<Instant>>,
+    /// Version of the value returned by our most recent `read()`
+    seen: Cell<u64>,
+    /// Number of versions we never observed, as of our most recent `read()`
+    skipped: Cell<u64>,
+    // pointer used so we get !Send/!Sync without the `unsafe impl` below.
     _marker: PhantomData<*const T>,
 }
 
 // SAFETY: if `T` is not `Sync` (ie: if it is a RefCell or has other non-thread safe mutability),
 // we can't send it between threads because we can't ensure that the reader won't mutate it.
+//
+// `Reader` is not `Sync`: `depth` & `epoch` are updated without synchronization by `read(&self)`.
 unsafe impl<T: Send + Sync> Send for Reader<T> {}
-unsafe impl<T: Send + Sync> Sync for Reader<T> {}
 
 impl<T> Clone for Reader<T> {
     fn clone(&self) -> Reader<T> {
-        Reader::<T>::new(self.shared.clone())
+        Reader::<T>::new(self.shared.clone(), self.seen.get())
     }
 }
 
 impl<T> Reader<T> {
-    fn new(shared: Arc<Shared<T>>) -> Reader<T> {
-        let epoch = Arc::new(atomic::AtomicUsize::new(0));
-        let epoch_index = shared.epochs.lock().unwrap().insert(epoch.clone());
+    fn new(shared: Arc<Shared<T>>, seen: u64) -> Reader<T> {
+        let epoch_index = shared.epochs.register(shared.monitor.stall.as_ref());
+        let epoch = shared.epochs.get(epoch_index) as *const Epoch;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(readers = shared.epochs.count(), "reader registered");
+        if let Some(metrics) = &shared.monitor.metrics {
+            metrics.reader_registered();
+        }
+        if let Some(observer) = &shared.monitor.observer {
+            observer.reader_registered();
+        }
 
         Reader {
             shared,
             epoch,
             epoch_index,
+            depth: Cell::new(0),
+            held_since: Cell::new(None),
+            seen: Cell::new(seen),
+            skipped: Cell::new(0),
             _marker: PhantomData,
         }
     }
 
+    fn epoch(&self) -> &Epoch {
+        // SAFETY: slots are never freed while `Shared::epochs` exists, and we hold a reference to
+        // `shared`.
+        unsafe { &*self.epoch }
+    }
+
+    /// Name this reader in [`StallDetector`] reports and [`Writer::readers()`]
+    pub fn set_name(&self, name: impl Into<String>) {
+        self.epoch().info.set_name(name.into());
+    }
+
+    /// Wait for the writer to publish a value newer than the one returned by our last `read()`
+    /// before this call
+    ///
+    /// Completes immediately if a newer value has already been published. This does not mark the
+    /// new value as seen, call `read()` to do that. Returns an error if the `Writer` has been
+    /// dropped and no newer value is available.
+    ///
+    /// The returned future does not depend on any particular executor, and is `Send` (if `T` is
+    /// `Send + Sync`) so it can be awaited in a spawned task.
+    pub fn changed(&self) -> Changed<'_, T> {
+        Changed {
+            shared: &self.shared,
+            seen: self.seen.get(),
+            key: None,
+        }
+    }
+
+    /// Has a value newer than the one returned by our last `read()` been published?
+    ///
+    /// This is a single atomic load, and does not enter a read section.
+    pub fn has_changed(&self) -> bool {
+        self.shared.has_changed(self.seen.get())
+    }
+
+    /// Read the value, but only if it has changed since our last `read()`
+    ///
+    /// Returns `None` without entering a read section if [`Reader::has_changed()`] is `false`.
+    pub fn read_if_changed(&self) -> Option<ReadGuard<'_, T>> {
+        if self.has_changed() {
+            Some(self.read())
+        } else {
+            None
+        }
+    }
+
+    /// Number of versions that were published and replaced without being observed by this reader
+    /// between its last two reads
+    ///
+    /// A non-zero value indicates this reader is not reading as often as the writer is writing.
+    pub fn skipped(&self) -> u64 {
+        self.skipped.get()
+    }
+
     /// Read the value
     ///
     /// To avoid leaking values, the return value of this function must be
     /// dropped.
     ///
+    /// This may be called again while a previous `ReadGuard` from this reader is still held
+    /// (ie: nested read sections), in which case the new guard may refer to a newer value. All
+    /// values read are kept alive until the last guard from this reader is dropped.
+    ///
     /// This function is conceptually an `srcu_read_lock()` and a
     /// `srcu_dereference()`. The `drop` of the return value (`ReadGuard`) is
     /// conceptually a `srcu_read_unlock()`.
-    pub fn read(&mut self) -> ReadGuard<T> {
-        // We're using `Relaxed` because all the ordering needed comes from the `Acquire` on
-        // `self.shared.active` below.
-        //
-        // Note: we split this `add` up because we don't need the consistency `add` provides (we're
-        // the only writer).
-        //
-        // TODO: check that compilers emit better code on various archs for this split version vs a
-        // merged `add` op.
-        let v = self.epoch.load(atomic::Ordering::Relaxed);
-        assert!(v & 1 == 0);
-
-        // NOTE: we can't call `read()` a second time if we leak the previous
-        // `ReadGuard`, so we can assume well behaved values.
-        self.epoch.store(v | 1, atomic::Ordering::Relaxed);
-
-        // Ensure `epoch` store is visible in other threads before we read
-        // `active` (so we don't get a garbage pointer)
-        // TODO: determine why AquRel isn't enough here
-        atomic::fence(atomic::Ordering::SeqCst);
+    pub fn read(&self) -> ReadGuard<'_, T> {
+        let node = self.lock();
+
+        // SAFETY: we've told the writer (via the epoch) that we're reading
+        // a value (by setting the low bit of the epoch), so it won't delete
+        // the value until we update our epoch. We only update our epoch
+        // when this `ReadGuard` is dropped. No `&mut`s are handed out to
+        // the data while it's in the `active` slot.
+        let node = unsafe { &*node };
+
+        ReadGuard {
+            reader: self,
+            data: &node.value,
+            version: node.version,
+            published_at: node.published_at,
+        }
+    }
+
+    /// Read the value, returning a guard that owns this `Reader`
+    ///
+    /// Unlike `read()`, the returned guard has no lifetime, so it can be stored in a struct or held
+    /// across an `.await` in a spawned task. Use [`OwnedReadGuard::into_reader()`] to end the read
+    /// section and get this `Reader` back.
+    ///
+    /// To avoid leaking values, the return value of this function must be
+    /// dropped (or converted back into a `Reader`).
+    pub fn read_owned(self) -> OwnedReadGuard<T> {
+        let node = self.lock();
+
+        // SAFETY: see `read()`. The node stays valid until we leave the read section, which
+        // happens when the `OwnedReadGuard` is dropped or converted back into a `Reader`.
+        let node = unsafe { &*node };
+
+        OwnedReadGuard {
+            data: &*node.value as *const T,
+            version: node.version,
+            published_at: node.published_at,
+            reader: self,
+        }
+    }
+
+    /// Enter a read section, returning the active node
+    ///
+    /// The node remains valid until `ReadLock::unlock()` is called.
+    fn lock(&self) -> *const Node<T> {
+        let depth = self.depth.get();
+        if depth == 0 {
+            // We're using `Relaxed` because all the ordering needed comes from the `Acquire` on
+            // `self.shared.active` below.
+            //
+            // Note: we split this `add` up because we don't need the consistency `add` provides
+            // (we're the only writer).
+            //
+            // TODO: check that compilers emit better code on various archs for this split version
+            // vs a merged `add` op.
+            let v = self.epoch().value.load(atomic::Ordering::Relaxed);
+            assert!(v & 1 == 0);
+
+            // NOTE: `depth` tracks leaked guards too, so we never get here with an odd epoch.
+            self.epoch().value.store(v | 1, atomic::Ordering::Relaxed);
+
+            // Ensure `epoch` store is visible in other threads before we read
+            // `active` (so we don't get a garbage pointer)
+            // TODO: determine why AquRel isn't enough here
+            //
+            // With `Builder::membarrier()`, this is only a compiler fence: the writer runs the
+            // full barrier on our behalf.
+            self.shared.fences.light();
+
+            if self.shared.monitor.metrics.is_some() {
+                self.held_since.set(Some(Instant::now()));
+            }
+        }
+        self.depth.set(depth + 1);
 
         // Pairs with a `Release` in `Writer::write()`, which ensures that we
         // see all the writes writer makes to things we load via `data`.
@@ -335,34 +1288,199 @@ impl<T> Reader<T> {
         // ensure that loads via it have a data dependency on other writes).
         // `Consume` isn't supported by current rust/loom though, so we use the
         // stronger `Acquire`.
-        let data = self.shared.active.load(atomic::Ordering::Acquire);
+        let node = self.shared.active.load(atomic::Ordering::Acquire);
 
-        ReadGuard {
-            reader: self,
-            // SAFETY: we've told the writer (via the epoch) that we're reading
-            // a value (by setting the low bit of the epoch), so it won't delete
-            // the value until we update our epoch. We only update our epoch
-            // when this `ReadGuard` is dropped. No `&mut`s are handed out to
-            // the data while it's in the `active` slot.
-            data: unsafe { &*data },
+        // SAFETY: we're in a read section, see `read()`.
+        let version = unsafe { (*node).version };
+        if version != self.seen.get() {
+            if let Some(observations) = &self.shared.monitor.observations {
+                observations.observed(version, Instant::now());
+            }
         }
+        self.skipped
+            .set(version.saturating_sub(self.seen.get()).saturating_sub(1));
+        self.seen.set(version);
+
+        node
     }
 }
 
 impl<T> Drop for Reader<T> {
     fn drop(&mut self) {
-        self.shared.epochs.lock().unwrap().remove(self.epoch_index);
+        let leaked = self.depth.get() != 0;
+        if leaked {
+            // A guard was leaked, the values it protects can never be collected.
+            self.epoch().info.set_leaked();
+        }
+        self.shared
+            .epochs
+            .unregister(self.epoch_index, leaked, |epoch| {
+                if let Some(metrics) = &self.shared.monitor.metrics {
+                    metrics.reader_deregistered(epoch);
+                }
+            });
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            readers = self.shared.epochs.count(),
+            leaked,
+            "reader dropped"
+        );
+        if let Some(observer) = &self.shared.monitor.observer {
+            observer.reader_dropped();
+        }
+    }
+}
+
+/// Future returned by [`Reader::changed()`]
+#[must_use = "futures do nothing unless polled"]
+pub struct Changed<'a, T> {
+    /// Borrowed from the `Reader`, which isn't `Sync`.
+    shared: &'a Shared<T>,
+    /// Version of the `Reader`'s last `read()` when this was created
+    seen: u64,
+    /// Our entry in `Shared::wakers`, if we've registered one
+    key: Option<usize>,
+}
+
+// SAFETY: we only use the atomics & `wakers` in `Shared`, never `prevs` (which makes it `!Sync`)
+// or any values.
+unsafe impl<'a, T: Send + Sync> Send for Changed<'a, T> {}
+unsafe impl<'a, T: Send + Sync> Sync for Changed<'a, T> {}
+
+impl<'a, T> Future for Changed<'a, T> {
+    type Output = Result<(), Closed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let shared = this.shared;
+
+        if !shared.has_changed(this.seen) && !shared.closed.load(atomic::Ordering::Relaxed) {
+            let mut wakers = shared.wakers.lock().unwrap();
+            match this.key {
+                Some(key) => {
+                    if !wakers[key].will_wake(cx.waker()) {
+                        wakers[key] = cx.waker().clone();
+                    }
+                }
+                None => {
+                    this.key = Some(wakers.insert(cx.waker().clone()));
+                    shared.waiting.fetch_add(1, atomic::Ordering::Relaxed);
+                }
+            }
+            drop(wakers);
+            shared.wake_pending();
+
+            // Check again now that we're registered, a write may have occured before the writer
+            // could see our waker. Pairs with the fence in `Shared::notify()`.
+            atomic::fence(atomic::Ordering::SeqCst);
+            if !shared.has_changed(this.seen) && !shared.closed.load(atomic::Ordering::Relaxed) {
+                return Poll::Pending;
+            }
+        }
+
+        if shared.has_changed(this.seen) {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Ready(Err(Closed))
+        }
+    }
+}
+
+impl<'a, T> Drop for Changed<'a, T> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            let shared = self.shared;
+            shared.wakers.lock().unwrap().remove(key);
+            shared.waiting.fetch_sub(1, atomic::Ordering::Relaxed);
+            shared.wake_pending();
+        }
     }
 }
 
+/// Error returned by [`Reader::changed()`] when the `Writer` has been dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Closed;
+
+impl fmt::Display for Closed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("writer dropped")
+    }
+}
+
+impl std::error::Error for Closed {}
+
 /// Allows access to the underlying value
 ///
 /// If this is leaked, the value it points to will also leak.
 ///
 /// This represents a particular version of the value.
 pub struct ReadGuard<'a, T> {
-    reader: &'a mut Reader<T>,
+    reader: &'a Reader<T>,
     data: &'a T,
+    version: u64,
+    published_at: Instant,
+}
+
+impl<'a, T> ReadGuard<'a, T> {
+    /// Version of the value this guard refers to
+    ///
+    /// See [`Writer::version()`].
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// When the value this guard refers to was published
+    ///
+    /// For the initial value, this is when the slot was created.
+    pub fn published_at(&self) -> Instant {
+        self.published_at
+    }
+
+    /// Make a new guard for a component of the value, keeping the read section held
+    ///
+    /// The read section is released when the returned `MappedReadGuard` is dropped.
+    ///
+    /// This is an associated function (ie: use `ReadGuard::map(guard, ...)`) so it doesn't
+    /// conflict with a method of the same name on `T`.
+    pub fn map<U: ?Sized, F>(this: Self, f: F) -> MappedReadGuard<'a, U>
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        // If `f` panics, `this` is dropped normally and releases the read section.
+        let data = f(this.data);
+        let this = ManuallyDrop::new(this);
+        MappedReadGuard {
+            reader: this.reader,
+            data,
+            version: this.version,
+            published_at: this.published_at,
+        }
+    }
+
+    /// Make a new guard for a component of the value that may not exist, keeping the read
+    /// section held
+    ///
+    /// If `f` returns `None`, the original guard is returned.
+    ///
+    /// This is an associated function (ie: use `ReadGuard::try_map(guard, ...)`) so it doesn't
+    /// conflict with a method of the same name on `T`.
+    pub fn try_map<U: ?Sized, F>(this: Self, f: F) -> Result<MappedReadGuard<'a, U>, Self>
+    where
+        F: FnOnce(&T) -> Option<&U>,
+    {
+        match f(this.data) {
+            Some(data) => {
+                let this = ManuallyDrop::new(this);
+                Ok(MappedReadGuard {
+                    reader: this.reader,
+                    data,
+                    version: this.version,
+                    published_at: this.published_at,
+                })
+            }
+            None => Err(this),
+        }
+    }
 }
 
 impl<'a, T> Deref for ReadGuard<'a, T> {
@@ -374,16 +1492,181 @@ impl<'a, T> Deref for ReadGuard<'a, T> {
 
 impl<'a, T> Drop for ReadGuard<'a, T> {
     fn drop(&mut self) {
+        self.reader.unlock();
+    }
+}
+
+/// Allows access to the underlying value, and owns the `Reader` it was read with
+///
+/// Created by [`Reader::read_owned()`]. If this is leaked, the value it points to will also leak.
+///
+/// This represents a particular version of the value.
+pub struct OwnedReadGuard<T> {
+    reader: Reader<T>,
+    data: *const T,
+    version: u64,
+    published_at: Instant,
+}
+
+// SAFETY: same requirements as `Reader`, we only hand out `&T`.
+unsafe impl<T: Send + Sync> Send for OwnedReadGuard<T> {}
+unsafe impl<T: Send + Sync> Sync for OwnedReadGuard<T> {}
+
+impl<T> OwnedReadGuard<T> {
+    /// Version of the value this guard refers to
+    ///
+    /// See [`Writer::version()`].
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// When the value this guard refers to was published
+    ///
+    /// For the initial value, this is when the slot was created.
+    pub fn published_at(&self) -> Instant {
+        self.published_at
+    }
+
+    /// End the read section, returning the `Reader` this guard was created from
+    ///
+    /// This is an associated function (ie: use `OwnedReadGuard::into_reader(guard)`) so it
+    /// doesn't conflict with a method of the same name on `T`.
+    pub fn into_reader(this: Self) -> Reader<T> {
+        let this = ManuallyDrop::new(this);
+        // SAFETY: `this` is never used again, and won't be dropped, so `reader` is moved out
+        // exactly once.
+        let reader = unsafe { ptr::read(&this.reader) };
+        reader.unlock();
+        reader
+    }
+}
+
+impl<T> Deref for OwnedReadGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: we hold the read section until dropped, see `Reader::read_owned()`.
+        unsafe { &*self.data }
+    }
+}
+
+impl<T> Drop for OwnedReadGuard<T> {
+    fn drop(&mut self) {
+        self.reader.unlock();
+    }
+}
+
+/// A guard for a component of a value, created by [`ReadGuard::map()`]
+///
+/// Holds the read section of the original `ReadGuard` until dropped. If this is leaked, the value
+/// it was mapped from will also leak.
+pub struct MappedReadGuard<'a, U: ?Sized> {
+    reader: &'a dyn ReadLock,
+    data: &'a U,
+    version: u64,
+    published_at: Instant,
+}
+
+impl<'a, U: ?Sized> MappedReadGuard<'a, U> {
+    /// Version of the value this guard was mapped from
+    ///
+    /// See [`Writer::version()`].
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// When the value this guard was mapped from was published
+    ///
+    /// For the initial value, this is when the slot was created.
+    pub fn published_at(&self) -> Instant {
+        self.published_at
+    }
+
+    /// Make a new guard for a component of this component, keeping the read section held
+    ///
+    /// See [`ReadGuard::map()`].
+    pub fn map<V: ?Sized, F>(this: Self, f: F) -> MappedReadGuard<'a, V>
+    where
+        F: FnOnce(&U) -> &V,
+    {
+        let data = f(this.data);
+        let this = ManuallyDrop::new(this);
+        MappedReadGuard {
+            reader: this.reader,
+            data,
+            version: this.version,
+            published_at: this.published_at,
+        }
+    }
+
+    /// Make a new guard for a component of this component that may not exist, keeping the read
+    /// section held
+    ///
+    /// See [`ReadGuard::try_map()`].
+    pub fn try_map<V: ?Sized, F>(this: Self, f: F) -> Result<MappedReadGuard<'a, V>, Self>
+    where
+        F: FnOnce(&U) -> Option<&V>,
+    {
+        match f(this.data) {
+            Some(data) => {
+                let this = ManuallyDrop::new(this);
+                Ok(MappedReadGuard {
+                    reader: this.reader,
+                    data,
+                    version: this.version,
+                    published_at: this.published_at,
+                })
+            }
+            None => Err(this),
+        }
+    }
+}
+
+impl<'a, U: ?Sized> Deref for MappedReadGuard<'a, U> {
+    type Target = U;
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl<'a, U: ?Sized> Drop for MappedReadGuard<'a, U> {
+    fn drop(&mut self) {
+        self.reader.unlock();
+    }
+}
+
+/// Type erased `Reader`, used so `MappedReadGuard` doesn't need the type of the original value
+trait ReadLock {
+    /// Leave the read section entered by `Reader::read()`
+    fn unlock(&self);
+}
+
+impl<T> ReadLock for Reader<T> {
+    fn unlock(&self) {
+        let depth = self.depth.get() - 1;
+        self.depth.set(depth);
+        if depth != 0 {
+            // An outer read section is still held.
+            return;
+        }
+
+        if let Some(since) = self.held_since.take() {
+            self.epoch().holds.record(since.elapsed());
+        }
+
         // NOTE: this split operation is ok because we are the only writer (others read this value).
         // This is split into 2 operations so that better code can be generated (ie: omitting CAS
         // on archs without atomic add opcodes).
-        let v = self.reader.epoch.load(atomic::Ordering::Relaxed);
+        let v = self.epoch().value.load(atomic::Ordering::Relaxed);
         assert!(v & 1 != 0);
-        self.reader.epoch.store(v + 1, atomic::Ordering::Release);
+        self.epoch().value.store(v + 1, atomic::Ordering::Release);
         // NOTE: adding a fence(SeqCst) here speeds up loom significantly,
         // implying not having it opens up many more execution variants. This
         // implies:
         // - omitting the fence may be useful for perf
         // - omitting the fence opens up lots of ways for our code to be wrong.
+        //
+        // We don't have one, so the writer can't rely on `wake_sync_waiter()` seeing that it's
+        // waiting.
+        self.shared.wake_sync_waiter();
     }
 }
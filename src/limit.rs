@@ -0,0 +1,65 @@
+//! Limiting the old values waiting to be collected, see [`Weigher`]
+use crate::Prevs;
+
+/// Measures the size of a value, for limiting the total size of old values
+///
+/// Installed with [`crate::Builder::max_pending_weight()`]. Called on old values waiting to be
+/// collected, which readers may be using concurrently, so it must not rely on exclusive access.
+///
+/// Implemented for any `Fn(&T) -> usize`.
+pub trait Weigher<T> {
+    /// Size of `val` in whatever unit the limit is expressed in (eg: bytes)
+    fn weigh(&self, val: &T) -> usize;
+}
+
+impl<T, F: Fn(&T) -> usize> Weigher<T> for F {
+    fn weigh(&self, val: &T) -> usize {
+        self(val)
+    }
+}
+
+/// Cap on the old values waiting to be collected
+pub(crate) struct Limit<T> {
+    max: usize,
+    /// If unset, each value weighs 1.
+    weigher: Option<Box<dyn Weigher<T> + Send>>,
+}
+
+impl<T> Limit<T> {
+    pub(crate) fn count(max: usize) -> Limit<T> {
+        Limit { max, weigher: None }
+    }
+
+    pub(crate) fn weight(max: usize, weigher: Box<dyn Weigher<T> + Send>) -> Limit<T> {
+        Limit {
+            max,
+            weigher: Some(weigher),
+        }
+    }
+
+    pub(crate) fn weigh(&self, val: &T) -> usize {
+        match &self.weigher {
+            Some(weigher) => weigher.weigh(val),
+            None => 1,
+        }
+    }
+
+    /// Total weight of the values in `prevs`
+    pub(crate) fn pending(&self, prevs: &Prevs<T>) -> usize {
+        match &self.weigher {
+            Some(weigher) => prevs
+                .iter()
+                .map(|retired| weigher.weigh(&retired.node.value))
+                .sum(),
+            None => prevs.len(),
+        }
+    }
+
+    /// Can a value of weight `weight` be added to `pending`?
+    ///
+    /// A value that is over the limit by itself is allowed when nothing else is pending, otherwise
+    /// it could never be replaced.
+    pub(crate) fn has_room(&self, pending: usize, weight: usize) -> bool {
+        pending == 0 || pending.saturating_add(weight) <= self.max
+    }
+}
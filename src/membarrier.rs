@@ -0,0 +1,131 @@
+//! Asymmetric fences using Linux's `membarrier()`, see [`crate::Builder::membarrier()`]
+use crate::atomic;
+
+/// How readers & writers order their epochs against `Shared::active`
+///
+/// The pairing is a store followed by a fence and a load on both sides: either side is
+/// guaranteed to see the other's store. Readers use `light()` and writers `heavy()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Fences {
+    /// Both sides use `fence(SeqCst)`.
+    Symmetric,
+    /// Readers only prevent the compiler from reordering, and writers have the kernel run a full
+    /// barrier on every thread of the process that is currently running (`membarrier()`), which
+    /// acts as a `fence(SeqCst)` at whatever point those threads are at.
+    Asymmetric,
+}
+
+impl Fences {
+    /// `Asymmetric` if requested and `membarrier()` is usable, otherwise `Symmetric`
+    pub(crate) fn new(asymmetric: bool) -> Fences {
+        if asymmetric && sys::register() {
+            Fences::Asymmetric
+        } else {
+            Fences::Symmetric
+        }
+    }
+
+    /// Fence in a reader, paired with `heavy()` in the writer
+    #[inline]
+    pub(crate) fn light(self) {
+        match self {
+            Fences::Symmetric => atomic::fence(atomic::Ordering::SeqCst),
+            Fences::Asymmetric => sys::light(),
+        }
+    }
+
+    /// Fence in the writer, paired with `light()` in readers
+    pub(crate) fn heavy(self) {
+        match self {
+            Fences::Symmetric => atomic::fence(atomic::Ordering::SeqCst),
+            Fences::Asymmetric => sys::heavy(),
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", not(loom)))]
+mod sys {
+    use std::{
+        io,
+        sync::{
+            atomic::{compiler_fence, Ordering},
+            OnceLock,
+        },
+    };
+
+    fn membarrier(cmd: libc::c_int) -> libc::c_long {
+        // SAFETY: `membarrier()` doesn't access memory we pass it, `flags` & `cpu_id` are unused
+        // by the commands we issue.
+        unsafe {
+            libc::syscall(
+                libc::SYS_membarrier,
+                cmd,
+                0 as libc::c_uint,
+                0 as libc::c_int,
+            )
+        }
+    }
+
+    /// Register the process for `MEMBARRIER_CMD_PRIVATE_EXPEDITED`, returning `false` if the
+    /// kernel (or a seccomp filter) doesn't allow it
+    ///
+    /// Only attempted once per process.
+    pub(super) fn register() -> bool {
+        static REGISTERED: OnceLock<bool> = OnceLock::new();
+        *REGISTERED.get_or_init(|| {
+            let supported = membarrier(libc::MEMBARRIER_CMD_QUERY);
+            supported >= 0
+                && supported & libc::MEMBARRIER_CMD_PRIVATE_EXPEDITED as libc::c_long != 0
+                && membarrier(libc::MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED) == 0
+        })
+    }
+
+    #[inline]
+    pub(super) fn light() {
+        compiler_fence(Ordering::SeqCst);
+    }
+
+    pub(super) fn heavy() {
+        // Can't fail once we've registered, and readers rely on it.
+        if membarrier(libc::MEMBARRIER_CMD_PRIVATE_EXPEDITED) != 0 {
+            panic!("membarrier() failed: {}", io::Error::last_os_error());
+        }
+    }
+}
+
+#[cfg(all(not(target_os = "linux"), not(loom)))]
+mod sys {
+    pub(super) fn register() -> bool {
+        false
+    }
+
+    pub(super) fn light() {
+        unreachable!("membarrier() is only used on Linux")
+    }
+
+    pub(super) fn heavy() {
+        unreachable!("membarrier() is only used on Linux")
+    }
+}
+
+// loom can't model a barrier run on other threads. `membarrier()` behaves as if each running
+// thread executed a `fence(SeqCst)` at its current point, in particular where it calls `light()`,
+// so model both sides as a `fence(SeqCst)`. That's identical to `Fences::Symmetric`: loom checks
+// that every `light()` is paired with a `heavy()`, but not that a compiler fence in readers is
+// enough, which relies on the kernel's guarantee.
+#[cfg(loom)]
+mod sys {
+    use crate::atomic;
+
+    pub(super) fn register() -> bool {
+        true
+    }
+
+    pub(super) fn light() {
+        atomic::fence(atomic::Ordering::SeqCst);
+    }
+
+    pub(super) fn heavy() {
+        atomic::fence(atomic::Ordering::SeqCst);
+    }
+}
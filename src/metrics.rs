@@ -0,0 +1,279 @@
+//! Counters describing a slot's activity, see [`MetricsSnapshot`]
+use crate::{Epoch, Epochs, Retired, Writer};
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Counters for a slot, enabled with [`crate::Builder::metrics()`]
+///
+/// Updated by the writer (and reclaimer thread) with `Relaxed` read-modify-writes. Readers only
+/// update their own `Holds`, so reading is still free of contended atomics.
+#[derive(Default)]
+pub(crate) struct Metrics {
+    writes: AtomicU64,
+    reclaimed: AtomicU64,
+    scans: AtomicU64,
+    readers_registered: AtomicU64,
+    readers_deregistered: AtomicU64,
+    reclaim_lag_nanos: AtomicU64,
+    reclaim_lag_max_nanos: AtomicU64,
+    /// Guard holds of readers that have been dropped.
+    dropped_holds: Holds,
+}
+
+/// Guard hold durations for a single reader
+///
+/// Only the reader updates these (with split loads & stores, like its epoch), anyone may read
+/// them.
+#[derive(Default)]
+pub(crate) struct Holds {
+    count: AtomicU64,
+    nanos: AtomicU64,
+    max_nanos: AtomicU64,
+}
+
+/// When the oldest of `retired` was replaced, if known
+pub(crate) fn oldest_retired<'a, T: 'a>(
+    retired: impl IntoIterator<Item = &'a Retired<T>>,
+) -> Option<Instant> {
+    retired.into_iter().filter_map(|r| r.retired_at).min()
+}
+
+fn nanos(d: Duration) -> u64 {
+    u64::try_from(d.as_nanos()).unwrap_or(u64::MAX)
+}
+
+impl Holds {
+    /// Record a read section that lasted `held`. Only the owning reader may call this.
+    pub(crate) fn record(&self, held: Duration) {
+        let held = nanos(held);
+        let count = self.count.load(Ordering::Relaxed);
+        self.count.store(count + 1, Ordering::Relaxed);
+        let total = self.nanos.load(Ordering::Relaxed);
+        self.nanos
+            .store(total.saturating_add(held), Ordering::Relaxed);
+        if held > self.max_nanos.load(Ordering::Relaxed) {
+            self.max_nanos.store(held, Ordering::Relaxed);
+        }
+    }
+
+    /// Clear the holds of a previous reader, before the slot is handed to a new one
+    pub(crate) fn reset(&self) {
+        self.count.store(0, Ordering::Relaxed);
+        self.nanos.store(0, Ordering::Relaxed);
+        self.max_nanos.store(0, Ordering::Relaxed);
+    }
+
+    /// Add `other` into `self`. Unlike `record()`, this may be called concurrently.
+    fn absorb(&self, other: &Holds) {
+        self.count
+            .fetch_add(other.count.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.nanos
+            .fetch_add(other.nanos.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.max_nanos
+            .fetch_max(other.max_nanos.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+}
+
+impl Metrics {
+    pub(crate) fn wrote(&self) {
+        self.writes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn scanned(&self) {
+        self.scans.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A value replaced at `retired_at` was collected
+    pub(crate) fn reclaimed(&self, retired_at: Option<Instant>) {
+        self.reclaimed.fetch_add(1, Ordering::Relaxed);
+        if let Some(retired_at) = retired_at {
+            let lag = nanos(retired_at.elapsed());
+            self.reclaim_lag_nanos.fetch_add(lag, Ordering::Relaxed);
+            self.reclaim_lag_max_nanos.fetch_max(lag, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn reader_registered(&self) {
+        self.readers_registered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn reader_deregistered(&self, epoch: &Epoch) {
+        self.readers_deregistered.fetch_add(1, Ordering::Relaxed);
+        self.dropped_holds.absorb(&epoch.holds);
+    }
+
+    pub(crate) fn snapshot(
+        &self,
+        epochs: &Epochs,
+        oldest_pending: Option<Instant>,
+    ) -> MetricsSnapshot {
+        let holds = Holds::default();
+        holds.absorb(&self.dropped_holds);
+        let mut readers = 0;
+        epochs.for_each(|_, epoch| {
+            holds.absorb(&epoch.holds);
+            readers += 1;
+        });
+
+        let writes = self.writes.load(Ordering::Relaxed);
+        let reclaimed = self.reclaimed.load(Ordering::Relaxed);
+        MetricsSnapshot {
+            writes,
+            reclaimed,
+            scans: self.scans.load(Ordering::Relaxed),
+            pending: writes.saturating_sub(reclaimed),
+            oldest_pending: oldest_pending.map(|at| at.elapsed()),
+            readers,
+            readers_registered: self.readers_registered.load(Ordering::Relaxed),
+            readers_deregistered: self.readers_deregistered.load(Ordering::Relaxed),
+            guard_holds: holds.count.load(Ordering::Relaxed),
+            guard_hold_time: Duration::from_nanos(holds.nanos.load(Ordering::Relaxed)),
+            guard_hold_max: Duration::from_nanos(holds.max_nanos.load(Ordering::Relaxed)),
+            reclaim_lag_time: Duration::from_nanos(self.reclaim_lag_nanos.load(Ordering::Relaxed)),
+            reclaim_lag_max: Duration::from_nanos(
+                self.reclaim_lag_max_nanos.load(Ordering::Relaxed),
+            ),
+        }
+    }
+}
+
+impl<T> Writer<T> {
+    /// Snapshot of this slot's metrics, or `None` if they weren't enabled with
+    /// [`crate::Builder::metrics()`]
+    ///
+    /// With a [`crate::Reclaimer`], this waits for the reclaimer thread to finish any scan in
+    /// progress.
+    pub fn metrics(&self) -> Option<MetricsSnapshot> {
+        let metrics = self.shared.monitor.metrics.as_ref()?;
+        let oldest = match &self.reclaimer {
+            Some(reclaimer) => reclaimer.handle().oldest_retired(),
+            None => oldest_retired(self.prevs().iter()),
+        };
+        Some(metrics.snapshot(&self.shared.epochs, oldest))
+    }
+}
+
+/// A point in time copy of a slot's metrics, returned by [`crate::Writer::metrics()`]
+///
+/// Counters (`*_total` in Prometheus output) only increase over the life of the slot.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// Values written
+    pub writes: u64,
+    /// Old values collected (returned, passed to a `Retire`, or dropped by a `Reclaimer`)
+    pub reclaimed: u64,
+    /// Scans for old values that are no longer in use
+    pub scans: u64,
+    /// Old values waiting to be collected
+    pub pending: u64,
+    /// How long the oldest value still waiting to be collected has been waiting. Values handed
+    /// to a `Reclaimer` are not included.
+    pub oldest_pending: Option<Duration>,
+    /// Readers that currently exist
+    pub readers: u64,
+    /// Readers created
+    pub readers_registered: u64,
+    /// Readers dropped
+    pub readers_deregistered: u64,
+    /// Read sections (outermost guards) that have ended
+    pub guard_holds: u64,
+    /// Total time read sections were held
+    pub guard_hold_time: Duration,
+    /// Longest time a single read section was held
+    pub guard_hold_max: Duration,
+    /// Total time between old values being replaced and collected
+    pub reclaim_lag_time: Duration,
+    /// Longest time between an old value being replaced and collected
+    pub reclaim_lag_max: Duration,
+}
+
+impl MetricsSnapshot {
+    /// Format as Prometheus text exposition format, with each sample labeled `slot="<slot>"`
+    ///
+    /// To export several slots, use [`MetricsSnapshot::write_prometheus()`]: concatenating the
+    /// output of this for each slot repeats the `# TYPE` lines, which Prometheus rejects.
+    pub fn to_prometheus(&self, slot: &str) -> String {
+        let mut out = String::new();
+        let _ = MetricsSnapshot::write_prometheus(&mut out, &[(slot, self)]);
+        out
+    }
+
+    /// Write the metrics of several slots to `out` in Prometheus text exposition format
+    ///
+    /// Each metric's `# TYPE` line is written once, followed by a sample for each of `slots`
+    /// labeled `slot="<name>"`.
+    pub fn write_prometheus(
+        out: &mut impl fmt::Write,
+        slots: &[(&str, &MetricsSnapshot)],
+    ) -> fmt::Result {
+        let slots: Vec<_> = slots
+            .iter()
+            .map(|(name, m)| (escape_label(name), m.samples()))
+            .collect();
+        let Some((_, first)) = slots.first() else {
+            return Ok(());
+        };
+        for (i, (name, kind, _)) in first.iter().enumerate() {
+            writeln!(out, "# TYPE local_rcu_{name} {kind}")?;
+            for (slot, samples) in &slots {
+                writeln!(out, "local_rcu_{name}{{slot=\"{slot}\"}} {}", samples[i].2)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Each metric's name (without the `local_rcu_` prefix), Prometheus type & value
+    fn samples(&self) -> [(&'static str, &'static str, String); 13] {
+        let secs = |d: Duration| d.as_secs_f64().to_string();
+        [
+            ("writes_total", "counter", self.writes.to_string()),
+            ("reclaimed_total", "counter", self.reclaimed.to_string()),
+            ("scans_total", "counter", self.scans.to_string()),
+            ("pending", "gauge", self.pending.to_string()),
+            (
+                "oldest_pending_seconds",
+                "gauge",
+                secs(self.oldest_pending.unwrap_or_default()),
+            ),
+            ("readers", "gauge", self.readers.to_string()),
+            (
+                "readers_registered_total",
+                "counter",
+                self.readers_registered.to_string(),
+            ),
+            (
+                "readers_deregistered_total",
+                "counter",
+                self.readers_deregistered.to_string(),
+            ),
+            ("guard_holds_total", "counter", self.guard_holds.to_string()),
+            (
+                "guard_hold_seconds_total",
+                "counter",
+                secs(self.guard_hold_time),
+            ),
+            ("guard_hold_max_seconds", "gauge", secs(self.guard_hold_max)),
+            (
+                "reclaim_lag_seconds_total",
+                "counter",
+                secs(self.reclaim_lag_time),
+            ),
+            (
+                "reclaim_lag_max_seconds",
+                "gauge",
+                secs(self.reclaim_lag_max),
+            ),
+        ]
+    }
+}
+
+/// Escape a Prometheus label value
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,36 @@
+//! Hooking into a slot's lifecycle events, see [`RcuObserver`]
+
+/// Callbacks for events in the life of a slot
+///
+/// Installed with [`crate::Builder::observer()`]. Every method has an empty default, so only the
+/// events of interest need to be implemented. Values are identified by their version (see
+/// [`crate::Writer::version()`]).
+///
+/// Callbacks run synchronously on the thread that caused the event: the writer, a reader being
+/// created or dropped, or a [`crate::Reclaimer`] thread. They should be quick, and must not write
+/// to the slot.
+pub trait RcuObserver {
+    /// `version` was published and is now visible to readers. Not called for the initial value.
+    ///
+    /// Called before the value it replaced is retired, and before readers waiting in
+    /// `Reader::changed()` are woken.
+    fn published(&self, version: u64) {
+        let _ = version;
+    }
+
+    /// `version` was replaced, while `readers` readers were in a read section that may be using it
+    fn retired(&self, version: u64, readers: usize) {
+        let _ = (version, readers);
+    }
+
+    /// `version` is no longer in use by any reader, and was collected
+    fn reclaimed(&self, version: u64) {
+        let _ = version;
+    }
+
+    /// A `Reader` was created
+    fn reader_registered(&self) {}
+
+    /// A `Reader` was dropped
+    fn reader_dropped(&self) {}
+}
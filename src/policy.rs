@@ -0,0 +1,112 @@
+//! When `Writer::write()` scans for old values, see [`ReclaimPolicy`]
+use std::time::{Duration, Instant};
+
+/// Decides when `Writer::write()` (or `SharedWriter::write()`) scans for old values that are no
+/// longer in use
+///
+/// Installed with [`crate::Builder::reclaim_policy()`], the default is [`Eager`]. Scanning costs
+/// time proportional to the number of old values and the readers that were active when each was
+/// replaced, while not scanning lets old values accumulate. `Writer::write_nosync()` never scans,
+/// and `Writer::write_with()` & `try_sync()`/`sync()` always do, regardless of the policy.
+///
+/// Implemented for any `FnMut(usize) -> bool`, which is called like `should_scan()`.
+pub trait ReclaimPolicy {
+    /// Called by `write()` after publishing a value. Return `true` to scan.
+    ///
+    /// `pending` is the number of old values waiting to be collected, including the one that was
+    /// just replaced.
+    fn should_scan(&mut self, pending: usize) -> bool;
+}
+
+impl<F: FnMut(usize) -> bool> ReclaimPolicy for F {
+    fn should_scan(&mut self, pending: usize) -> bool {
+        self(pending)
+    }
+}
+
+/// Scan on every write
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Eager;
+
+impl ReclaimPolicy for Eager {
+    fn should_scan(&mut self, _pending: usize) -> bool {
+        true
+    }
+}
+
+/// Scan on every `n`th write
+#[derive(Debug, Clone)]
+pub struct EveryNWrites {
+    n: usize,
+    writes: usize,
+}
+
+impl EveryNWrites {
+    /// Scan once every `n` writes. `n` of 0 is treated as 1.
+    pub fn new(n: usize) -> EveryNWrites {
+        EveryNWrites {
+            n: n.max(1),
+            writes: 0,
+        }
+    }
+}
+
+impl ReclaimPolicy for EveryNWrites {
+    fn should_scan(&mut self, _pending: usize) -> bool {
+        self.writes += 1;
+        if self.writes < self.n {
+            return false;
+        }
+        self.writes = 0;
+        true
+    }
+}
+
+/// Scan once more than `threshold` old values are waiting to be collected
+#[derive(Debug, Clone)]
+pub struct PendingThreshold {
+    threshold: usize,
+}
+
+impl PendingThreshold {
+    /// Scan when more than `threshold` old values are pending
+    pub fn new(threshold: usize) -> PendingThreshold {
+        PendingThreshold { threshold }
+    }
+}
+
+impl ReclaimPolicy for PendingThreshold {
+    fn should_scan(&mut self, pending: usize) -> bool {
+        pending > self.threshold
+    }
+}
+
+/// Scan on the first write after `interval` has elapsed since the last scan
+#[derive(Debug, Clone)]
+pub struct Interval {
+    interval: Duration,
+    last: Option<Instant>,
+}
+
+impl Interval {
+    /// Scan at most once per `interval`. The first write always scans.
+    pub fn new(interval: Duration) -> Interval {
+        Interval {
+            interval,
+            last: None,
+        }
+    }
+}
+
+impl ReclaimPolicy for Interval {
+    fn should_scan(&mut self, _pending: usize) -> bool {
+        let now = Instant::now();
+        if let Some(last) = self.last {
+            if now.saturating_duration_since(last) < self.interval {
+                return false;
+            }
+        }
+        self.last = Some(now);
+        true
+    }
+}
@@ -0,0 +1,192 @@
+//! Old values waiting to be collected, grouped by the readers that may still use them, see
+//! [`Prevs`]
+use crate::{Epochs, Holdout, Node, Retired};
+use std::{mem::MaybeUninit, time::Instant};
+
+/// Most emptied batches & node allocations kept for reuse.
+const MAX_SPARE: usize = 64;
+
+/// Values that have been replaced, and may still be in use by readers
+///
+/// Values are grouped into batches (grace periods): consecutive values that are waiting on the
+/// same readers share a single list of those readers, which is scanned once for the whole batch.
+/// When a reader is slow to leave its read section, every value replaced in the meantime ends up
+/// in the same batch, so scanning costs the number of batches times the number of readers
+/// instead of the number of values times the number of readers.
+///
+/// Emptied batches (with their buffers) and the allocations of collected nodes are kept for reuse,
+/// so a `Writer` that keeps up with its readers publishes & collects without allocating anything
+/// other than the values themselves.
+pub(crate) struct Prevs<T> {
+    /// Oldest first.
+    batches: Vec<Batch<T>>,
+    /// Number of values in all `batches`.
+    len: usize,
+    /// Empty batches, reused by `push()`.
+    spare_batches: Vec<Batch<T>>,
+    /// Allocations of collected nodes, reused by `node()`. Each must stay a separate allocation.
+    #[allow(clippy::vec_box)]
+    spare_nodes: Vec<Box<MaybeUninit<Node<T>>>>,
+}
+
+/// Values that can be collected once all of `readers` have left the read section they were in
+pub(crate) struct Batch<T> {
+    /// Oldest first.
+    pub(crate) values: Vec<Retired<T>>,
+    /// Readers that were in a read section when one of `values` was replaced, with their epoch at
+    /// the time. Readers that have since moved to a new epoch are removed by `Prevs::collect()`.
+    ///
+    /// Sorted by slot index.
+    pub(crate) readers: Vec<Holdout>,
+}
+
+impl<T> Default for Prevs<T> {
+    fn default() -> Self {
+        Prevs::new()
+    }
+}
+
+impl<T> Prevs<T> {
+    pub(crate) fn new() -> Prevs<T> {
+        Prevs {
+            batches: Vec::new(),
+            len: 0,
+            spare_batches: Vec::new(),
+            spare_nodes: Vec::new(),
+        }
+    }
+
+    /// Number of values
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// All values, oldest first
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Retired<T>> {
+        self.batches.iter().flat_map(|batch| &batch.values)
+    }
+
+    pub(crate) fn batches(&self) -> &[Batch<T>] {
+        &self.batches
+    }
+
+    pub(crate) fn batches_mut(&mut self) -> &mut [Batch<T>] {
+        &mut self.batches
+    }
+
+    /// Box `node`, reusing the allocation of a collected node if there is one
+    pub(crate) fn node(&mut self, node: Node<T>) -> Box<Node<T>> {
+        match self.spare_nodes.pop() {
+            Some(mut spare) => {
+                spare.write(node);
+                // SAFETY: just initialized, and `MaybeUninit<Node<T>>` has the same layout as
+                // `Node<T>`.
+                unsafe { Box::from_raw(Box::into_raw(spare).cast::<Node<T>>()) }
+            }
+            None => Box::new(node),
+        }
+    }
+
+    /// Add a value that was just replaced, recording the readers currently in a read section
+    /// (see `Epochs::holdouts()`). Returns the number of those readers.
+    ///
+    /// If the readers still holding the newest batch are exactly the current ones, the value joins
+    /// it.
+    pub(crate) fn push(&mut self, retired: Retired<T>, epochs: &Epochs) -> usize {
+        self.len += 1;
+        let mut batch = self.spare_batches.pop().unwrap_or_else(|| Batch {
+            values: Vec::new(),
+            readers: Vec::new(),
+        });
+        epochs.holdouts(&mut batch.readers);
+        let holdouts = batch.readers.len();
+
+        if let Some(last) = self.batches.last_mut() {
+            last.readers.retain(|holdout| holdout.is_held(epochs));
+            if last.readers == batch.readers {
+                last.values.push(retired);
+                self.spare_batches.push(batch);
+                return holdouts;
+            }
+        }
+        batch.values.push(retired);
+        self.batches.push(batch);
+        holdouts
+    }
+
+    /// Move all of `other`'s values after ours
+    pub(crate) fn append(&mut self, other: &mut Prevs<T>) {
+        self.batches.append(&mut other.batches);
+        self.len += other.len;
+        other.len = 0;
+    }
+
+    /// Move `other`'s spare batches & node allocations to us, keeping at most `MAX_SPARE` of each
+    ///
+    /// Used when values are collected by a different `Prevs` than the one they were pushed to.
+    pub(crate) fn take_spares(&mut self, other: &mut Prevs<T>) {
+        let batches = MAX_SPARE.saturating_sub(self.spare_batches.len());
+        let batches = other.spare_batches.len().min(batches);
+        let from = other.spare_batches.len() - batches;
+        self.spare_batches.extend(other.spare_batches.drain(from..));
+
+        let nodes = MAX_SPARE.saturating_sub(self.spare_nodes.len());
+        let nodes = other.spare_nodes.len().min(nodes);
+        let from = other.spare_nodes.len() - nodes;
+        self.spare_nodes.extend(other.spare_nodes.drain(from..));
+    }
+
+    /// Remove the values no longer in use by any reader, passing each to `f` (along with its
+    /// version & when it was replaced) oldest first
+    ///
+    /// Batches left waiting on the same readers are merged.
+    pub(crate) fn collect(
+        &mut self,
+        epochs: &Epochs,
+        mut f: impl FnMut(Box<T>, u64, Option<Instant>),
+    ) {
+        let Prevs {
+            batches,
+            len,
+            spare_batches,
+            spare_nodes,
+        } = self;
+        let mut kept = 0;
+        for i in 0..batches.len() {
+            batches[i].readers.retain(|holdout| holdout.is_held(epochs));
+
+            if batches[i].readers.is_empty() {
+                // TODO: consider if we require a fence here to ensure all reads
+                // have occured before this point.
+
+                // No readers are left (because all have moved to a new epoch). We're removing
+                // the values from `batches` too, so nothing else refers to them.
+                *len -= batches[i].values.len();
+                for retired in batches[i].values.drain(..) {
+                    let version = retired.node.version;
+                    let (value, spare) = Node::into_value(retired.node);
+                    if spare_nodes.len() < MAX_SPARE {
+                        spare_nodes.push(spare);
+                    }
+                    f(value, version, retired.retired_at);
+                }
+            } else if kept != 0 && batches[kept - 1].readers == batches[i].readers {
+                let (head, tail) = batches.split_at_mut(i);
+                head[kept - 1].values.append(&mut tail[0].values);
+            } else {
+                batches.swap(kept, i);
+                kept += 1;
+            }
+        }
+        for mut batch in batches.drain(kept..) {
+            if spare_batches.len() < MAX_SPARE {
+                batch.readers.clear();
+                spare_batches.push(batch);
+            }
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! Collecting old values on a background thread, see [`Reclaimer`]
+use crate::{
+    metrics, retire_or_push, thread, Arc, Condvar, Epochs, Monitor, Mutex, Outstanding, Prevs,
+    RetireFn, Shared,
+};
+use std::{
+    sync::PoisonError,
+    time::{Duration, Instant},
+};
+
+/// Moves the collection of old values onto a dedicated thread
+///
+/// Installed with [`crate::Builder::reclaimer()`]. With a `Reclaimer`, `Writer::write()` only
+/// publishes the new value and hands the old one to the reclaimer thread, which scans for values
+/// no longer in use by readers after each write and every [`Reclaimer::interval()`] while old
+/// values remain. Those values are dropped on the reclaimer thread, or passed to the
+/// [`crate::Retire`] if one is configured.
+///
+/// As a result `write()` & `try_sync()` never return old values. `sync()` (and similar) still
+/// wait for all old values to be collected, and return them if there is no `Retire`.
+#[derive(Debug, Clone)]
+pub struct Reclaimer {
+    interval: Duration,
+    name: Option<String>,
+}
+
+impl Default for Reclaimer {
+    fn default() -> Self {
+        Reclaimer::new()
+    }
+}
+
+impl Reclaimer {
+    /// Create a `Reclaimer` that scans every 10ms while old values remain
+    pub fn new() -> Reclaimer {
+        Reclaimer {
+            interval: Duration::from_millis(10),
+            name: None,
+        }
+    }
+
+    /// How long to wait between scans while old values are still in use by readers
+    ///
+    /// When there are no old values, the reclaimer thread sleeps until the next write.
+    pub fn interval(mut self, interval: Duration) -> Reclaimer {
+        self.interval = interval;
+        self
+    }
+
+    /// Name of the reclaimer thread
+    pub fn name(mut self, name: impl Into<String>) -> Reclaimer {
+        self.name = Some(name.into());
+        self
+    }
+}
+
+/// A running reclaimer thread, owned by the `Writer`
+pub(crate) struct Background<T> {
+    handle: Arc<Handle<T>>,
+    thread: thread::JoinHandle<()>,
+}
+
+/// State shared with the reclaimer thread
+pub(crate) struct Handle<T> {
+    /// Old values handed off by the `Writer`, not yet picked up by the reclaimer thread.
+    ///
+    /// Only held for long enough to push or take entries, so the `Writer` is never blocked by a
+    /// scan.
+    queue: Mutex<Queue<T>>,
+    /// Signalled when `queue` changes.
+    wake: Condvar,
+
+    /// Held while scanning for values to collect.
+    ///
+    /// Collected values are dropped (or passed to `retire`) after releasing it, so the `Writer`
+    /// inspecting `state` never waits for them.
+    state: Mutex<State<T>>,
+
+    /// Taken from the `Writer` when the reclaimer thread was spawned.
+    retire: Mutex<Option<RetireFn<T>>>,
+
+    epochs: Arc<Epochs>,
+    monitor: Arc<Monitor>,
+}
+
+struct Queue<T> {
+    retired: Prevs<T>,
+    stop: bool,
+}
+
+struct State<T> {
+    /// Old values that may still be in use by readers.
+    prevs: Prevs<T>,
+}
+
+impl<T: Send + 'static> Background<T> {
+    /// Start a reclaimer thread configured by `config`
+    pub(crate) fn spawn(
+        config: &Reclaimer,
+        retire: Option<RetireFn<T>>,
+        shared: &Shared<T>,
+    ) -> Background<T> {
+        let handle = Arc::new(Handle {
+            queue: Mutex::new(Queue {
+                retired: Prevs::new(),
+                stop: false,
+            }),
+            wake: Condvar::new(),
+            state: Mutex::new(State {
+                prevs: Prevs::new(),
+            }),
+            retire: Mutex::new(retire),
+            epochs: shared.epochs.clone(),
+            monitor: shared.monitor.clone(),
+        });
+
+        let mut builder = thread::Builder::new();
+        if let Some(name) = &config.name {
+            builder = builder.name(name.clone());
+        }
+        let interval = config.interval;
+        let thread = {
+            let handle = handle.clone();
+            builder
+                .spawn(move || handle.run(interval))
+                .expect("failed to spawn reclaimer thread")
+        };
+
+        Background { handle, thread }
+    }
+}
+
+impl<T> Handle<T> {
+    fn run(&self, interval: Duration) {
+        let mut retired = Prevs::new();
+        // Values collected by the last scan, reused so scanning doesn't allocate.
+        let mut v = Vec::new();
+        let mut idle = true;
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            if queue.stop {
+                return;
+            }
+
+            if queue.retired.is_empty() {
+                queue = if idle {
+                    self.wake.wait(queue).unwrap()
+                } else {
+                    self.wake.wait_timeout(queue, interval).unwrap().0
+                };
+                if queue.stop {
+                    return;
+                }
+            }
+
+            retired.append(&mut queue.retired);
+            drop(queue);
+
+            {
+                let mut state = self.state.lock().unwrap();
+                let prevs = &mut state.prevs;
+                prevs.append(&mut retired);
+                Shared::try_sync(prevs, &self.epochs, &self.monitor, |val| v.push(val));
+                idle = prevs.is_empty();
+            }
+
+            // Without a `Retire`, the collected values are dropped here, on this thread.
+            match &mut *self.retire.lock().unwrap() {
+                Some(retire) => v.drain(..).for_each(|val| retire.retire(val)),
+                None => v.clear(),
+            }
+
+            queue = self.queue.lock().unwrap();
+        }
+    }
+
+    /// Hand all entries in `prevs` to the reclaimer thread
+    pub(crate) fn retire(&self, prevs: &mut Prevs<T>) {
+        self.queue.lock().unwrap().retired.append(prevs);
+        self.wake.notify_one();
+    }
+
+    /// Have the reclaimer thread scan for old values now
+    pub(crate) fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    pub(crate) fn has_old_values(&self) -> bool {
+        !self.queue.lock().unwrap().retired.is_empty()
+            || !self.state.lock().unwrap().prevs.is_empty()
+    }
+
+    /// Sum of `f` over the old values handed to the reclaimer thread that it hasn't collected
+    pub(crate) fn pending(&self, f: impl Fn(&Prevs<T>) -> usize) -> usize {
+        let state = self.state.lock().unwrap();
+        f(&state.prevs) + f(&self.queue.lock().unwrap().retired)
+    }
+
+    /// When the oldest value handed to the reclaimer thread that it hasn't collected was replaced
+    pub(crate) fn oldest_retired(&self) -> Option<Instant> {
+        let state = self.state.lock().unwrap();
+        let queue = self.queue.lock().unwrap();
+        metrics::oldest_retired(state.prevs.iter().chain(queue.retired.iter()))
+    }
+
+    /// Collect old values on the calling thread until `done` returns `true` for the remaining ones
+    /// or `deadline` passes
+    ///
+    /// Values are passed to the `Retire`, or pushed into `v` if there isn't one.
+    pub(crate) fn sync_until(
+        &self,
+        shared: &Shared<T>,
+        deadline: Option<Instant>,
+        v: &mut Vec<Box<T>>,
+        done: impl FnMut(&Prevs<T>) -> bool,
+    ) -> Result<(), Outstanding> {
+        // Holding `state` keeps the reclaimer thread (and anyone else syncing) out of `prevs` while
+        // we wait.
+        let mut state = self.state.lock().unwrap();
+        let prevs = &mut state.prevs;
+        prevs.append(&mut self.queue.lock().unwrap().retired);
+        let mut retire = self.retire.lock().unwrap();
+        shared.sync_until(prevs, deadline, retire_or_push(&mut retire, v), done)
+    }
+}
+
+impl<T> Background<T> {
+    pub(crate) fn handle(&self) -> &Arc<Handle<T>> {
+        &self.handle
+    }
+
+    /// Stop the reclaimer thread, moving any old values it hasn't collected into `prevs`
+    pub(crate) fn shutdown(self, prevs: &mut Prevs<T>) {
+        let Background { handle, thread } = self;
+        handle
+            .queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .stop = true;
+        handle.wake.notify_one();
+        // A panic in a `Retire` has already been reported by the reclaimer thread.
+        let _ = thread.join();
+
+        let mut queue = handle.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut state = handle.state.lock().unwrap_or_else(PoisonError::into_inner);
+        prevs.append(&mut state.prevs);
+        prevs.append(&mut queue.retired);
+    }
+}
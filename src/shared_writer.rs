@@ -0,0 +1,396 @@
+//! Multiple producer support, see [`SharedWriter`]
+use crate::{
+    atomic, metrics, reclaimer, retire_or_push, Arc, MetricsSnapshot, Mutex, Prevs, Reader,
+    RetireFn, Shared, SyncTimeout, TryWriteError, Writer,
+};
+use std::{
+    sync::{PoisonError, TryLockError},
+    time::{Duration, Instant},
+};
+
+/// Writer for a slot that may be cloned and shared between threads, allowing multiple producers
+///
+/// Created by [`crate::mpmc_slot()`] or [`Writer::into_shared()`].
+///
+/// Publishing a value takes an internal lock that is only held while the new value is stored and
+/// the current readers are recorded. Writes are ordered by that lock: each write is assigned the
+/// next version number, and the value with the highest version (the one that acquired the lock
+/// last) is the one readers will see. Concurrent writes from different threads are not otherwise
+/// ordered.
+///
+/// Collecting old values (and calling the [`crate::Retire`], if any) uses a seperate lock. Only 1
+/// producer scans for old values at a time, `write()` & `try_sync()` skip the scan if another
+/// producer is already scanning. Old values may be returned by any producer, not necessarily the
+/// one that replaced them.
+pub struct SharedWriter<T> {
+    inner: Arc<Inner<T>>,
+}
+
+// SAFETY: same as `Writer`. `Shared::prevs` is only accessed by the `Writer` (while holding
+// `Inner::writer`) and by our `Drop`.
+unsafe impl<T: Send + Sync> Send for SharedWriter<T> {}
+unsafe impl<T: Send + Sync> Sync for SharedWriter<T> {}
+
+struct Inner<T> {
+    shared: Arc<Shared<T>>,
+
+    /// Held while publishing a new value.
+    writer: Mutex<Writer<T>>,
+
+    /// Old values that have been replaced by a producer, but not yet moved into `reclaim`.
+    ///
+    /// Only held for long enough to push or take entries, so producers publishing values are not
+    /// blocked by a producer that is scanning `reclaim`. Also passes the spare allocations of
+    /// collected values from `reclaim` back to the `Writer`.
+    retired: Mutex<Prevs<T>>,
+
+    /// Held while scanning for values to return.
+    reclaim: Mutex<Reclaim<T>>,
+
+    /// Number of values left in `reclaim` by the last scan, so producers can tell the
+    /// `ReclaimPolicy` how many values are pending without waiting for a scan to finish.
+    reclaim_pending: atomic::AtomicUsize,
+
+    /// The `Writer`'s reclaimer thread, if any. When set, `retired` & `reclaim` are never used:
+    /// the `Writer` hands every old value to the reclaimer thread.
+    reclaimer: Option<Arc<reclaimer::Handle<T>>>,
+}
+
+struct Reclaim<T> {
+    /// Old values that may still be in use by readers.
+    prevs: Prevs<T>,
+    /// Taken from the `Writer` when it was converted, see `Writer::retire`.
+    retire: Option<RetireFn<T>>,
+}
+
+impl<T> Clone for SharedWriter<T> {
+    fn clone(&self) -> SharedWriter<T> {
+        SharedWriter {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        // Readers may still be using the old values we're tracking. Give them back to the
+        // `Writer`, which keeps them in `Shared` until all readers are gone.
+        let writer = self
+            .writer
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        let prevs = writer.prevs_mut();
+        prevs.append(
+            &mut self
+                .reclaim
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .prevs,
+        );
+        prevs.append(
+            self.retired
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+    }
+}
+
+impl<T> Writer<T> {
+    /// Convert this `Writer` into a [`SharedWriter`] so multiple threads may write values
+    ///
+    /// Any old values this `Writer` was waiting to collect are transfered to the `SharedWriter`,
+    /// along with its [`crate::Retire`] (if any). A [`crate::Reclaimer`] keeps collecting old
+    /// values for all producers, the [`crate::ReclaimPolicy`] decides when
+    /// [`SharedWriter::write()`] scans, and the limit on old values (see
+    /// [`crate::Builder::max_pending()`]) is enforced by [`SharedWriter::try_write()`] &
+    /// [`SharedWriter::write_bounded()`].
+    pub fn into_shared(mut self) -> SharedWriter<T> {
+        let pending = self.prevs().len();
+        let reclaim = Reclaim {
+            prevs: std::mem::take(self.prevs_mut()),
+            retire: self.retire.take(),
+        };
+        let reclaimer = self.reclaimer.as_ref().map(|r| r.handle().clone());
+        SharedWriter {
+            inner: Arc::new(Inner {
+                shared: self.shared.clone(),
+                reclaimer,
+                writer: Mutex::new(self),
+                retired: Mutex::new(Prevs::new()),
+                reclaim: Mutex::new(reclaim),
+                reclaim_pending: atomic::AtomicUsize::new(pending),
+            }),
+        }
+    }
+}
+
+impl<T> SharedWriter<T> {
+    /// Obtain a reader for the value stored by this writer
+    ///
+    /// See [`Writer::reader()`].
+    pub fn reader(&self) -> Reader<T> {
+        let shared = &self.inner.shared;
+        Reader::new(shared.clone(), self.version())
+    }
+
+    /// Version of the most recently published value
+    ///
+    /// Other producers may publish new values at any time, so this may be out of date as soon as
+    /// it is returned.
+    pub fn version(&self) -> u64 {
+        self.inner.shared.version.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Write a new value, returning any old values that are no longer in use
+    ///
+    /// See [`Writer::write()`]. Whether old values are scanned for is decided by the
+    /// [`crate::ReclaimPolicy`], which is shared by all producers. If another producer is
+    /// currently scanning for old values, no scan is done and no old values are returned.
+    pub fn write(&self, val: Box<T>) -> Vec<Box<T>> {
+        if self.publish(val) {
+            self.try_sync()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Write a new value, without checking if any old values are no longer in use
+    ///
+    /// See [`Writer::write_nosync()`].
+    pub fn write_nosync(&self, val: Box<T>) {
+        self.publish(val);
+    }
+
+    /// Write a new value unless the limit on old values waiting to be collected would be exceeded
+    ///
+    /// See [`Writer::try_write()`]. The limit covers the old values replaced by all producers.
+    /// Other producers can't publish while this checks the limit, and this waits for any producer
+    /// that is currently scanning for old values.
+    pub fn try_write(&self, val: Box<T>) -> Result<Vec<Box<T>>, TryWriteError<T>> {
+        let mut v = Vec::new();
+        let scan = {
+            let mut writer = self.inner.writer.lock().unwrap();
+            if let Some(pending) = self.make_room(&mut writer, Some(Instant::now()), &mut v) {
+                return Err(TryWriteError {
+                    value: val,
+                    reclaimed: v,
+                    pending,
+                });
+            }
+            self.publish_locked(&mut writer, val)
+        };
+
+        if scan {
+            v.extend(self.try_sync());
+        }
+        Ok(v)
+    }
+
+    /// Write a new value, first waiting until the limit on old values waiting to be collected
+    /// allows it
+    ///
+    /// See [`Writer::write_bounded()`]. Other producers can't publish while this waits.
+    pub fn write_bounded(&self, val: Box<T>) -> Vec<Box<T>> {
+        let mut v = Vec::new();
+        let scan = {
+            let mut writer = self.inner.writer.lock().unwrap();
+            self.make_room(&mut writer, None, &mut v);
+            self.publish_locked(&mut writer, val)
+        };
+
+        if scan {
+            v.extend(self.try_sync());
+        }
+        v
+    }
+
+    /// Collect old values until the current value can be replaced without exceeding the
+    /// `Writer`'s limit, or `deadline` passes. Returns the pending weight if there still isn't
+    /// room.
+    ///
+    /// Holding `writer` keeps other producers from adding old values meanwhile.
+    fn make_room(
+        &self,
+        writer: &mut Writer<T>,
+        deadline: Option<Instant>,
+        v: &mut Vec<Box<T>>,
+    ) -> Option<usize> {
+        if writer.reclaimer.is_some() {
+            // All old values are with the reclaimer thread, which the `Writer` already handles.
+            return writer.make_room(deadline, v);
+        }
+
+        let limit = writer.limit.as_ref()?;
+        let weight = limit.weigh(writer.read());
+
+        // Holding `reclaim` ensures we're the only thread in `Shared::sync_until()`.
+        let mut reclaim = self.inner.reclaim.lock().unwrap();
+        let Reclaim { prevs, retire } = &mut *reclaim;
+        self.take_retired(prevs);
+
+        let mut pending = limit.pending(prevs);
+        let mut r = None;
+        if !limit.has_room(pending, weight) {
+            let done = |prevs: &Prevs<T>| {
+                pending = limit.pending(prevs);
+                limit.has_room(pending, weight)
+            };
+            let synced =
+                self.inner
+                    .shared
+                    .sync_until(prevs, deadline, retire_or_push(retire, v), done);
+            if synced.is_err() {
+                r = Some(pending);
+            }
+        }
+
+        self.inner
+            .reclaim_pending
+            .store(prevs.len(), atomic::Ordering::Relaxed);
+        r
+    }
+
+    /// Publish `val`, returning `true` if the `ReclaimPolicy` wants `write()` to scan
+    fn publish(&self, val: Box<T>) -> bool {
+        let mut writer = self.inner.writer.lock().unwrap();
+        self.publish_locked(&mut writer, val)
+    }
+
+    fn publish_locked(&self, writer: &mut Writer<T>, val: Box<T>) -> bool {
+        writer.write_nosync(val);
+
+        // `write_nosync()` leaves the value it replaced in the `Writer`'s `prevs`, which otherwise
+        // only holds spare allocations for the next write.
+        let prevs = writer.prevs_mut();
+        let mut retired = self.inner.retired.lock().unwrap();
+        retired.append(prevs);
+        prevs.take_spares(&mut retired);
+        let pending = retired.len() + self.inner.reclaim_pending.load(atomic::Ordering::Relaxed);
+        drop(retired);
+
+        writer.reclaimer.is_none() && writer.policy.should_scan(pending)
+    }
+
+    /// Are there any old values waiting to be collected?
+    ///
+    /// See [`Writer::has_old_values()`].
+    pub fn has_old_values(&self) -> bool {
+        if let Some(reclaimer) = &self.inner.reclaimer {
+            return reclaimer.has_old_values();
+        }
+
+        !self.inner.retired.lock().unwrap().is_empty()
+            || !self.inner.reclaim.lock().unwrap().prevs.is_empty()
+    }
+
+    /// Snapshot of this slot's metrics, see [`Writer::metrics()`]
+    ///
+    /// Waits for any producer that is currently scanning for old values.
+    pub fn metrics(&self) -> Option<MetricsSnapshot> {
+        let shared = &self.inner.shared;
+        let metrics = shared.monitor.metrics.as_ref()?;
+        let oldest = match &self.inner.reclaimer {
+            Some(reclaimer) => reclaimer.oldest_retired(),
+            None => {
+                let reclaim = self.inner.reclaim.lock().unwrap();
+                let retired = self.inner.retired.lock().unwrap();
+                metrics::oldest_retired(reclaim.prevs.iter().chain(retired.iter()))
+            }
+        };
+        Some(metrics.snapshot(&shared.epochs, oldest))
+    }
+
+    /// Check if we can release previous values and return them
+    ///
+    /// See [`Writer::try_sync()`]. Returns nothing if another producer is currently scanning for
+    /// old values.
+    pub fn try_sync(&self) -> Vec<Box<T>> {
+        if let Some(reclaimer) = &self.inner.reclaimer {
+            reclaimer.wake();
+            return Vec::new();
+        }
+
+        let mut reclaim = match self.inner.reclaim.try_lock() {
+            Err(TryLockError::WouldBlock) => return Vec::new(),
+            r => r.unwrap(),
+        };
+        let Reclaim { prevs, retire } = &mut *reclaim;
+
+        let mut v = Vec::new();
+        self.take_retired(prevs);
+        Shared::try_sync(
+            prevs,
+            &self.inner.shared.epochs,
+            &self.inner.shared.monitor,
+            retire_or_push(retire, &mut v),
+        );
+        self.inner
+            .reclaim_pending
+            .store(prevs.len(), atomic::Ordering::Relaxed);
+        v
+    }
+
+    /// Wait until all old values replaced before this call are collected
+    ///
+    /// See [`Writer::sync()`]. Waits for any other producer that is currently scanning for old
+    /// values.
+    pub fn sync(&self) -> Vec<Box<T>> {
+        match self.sync_until(None) {
+            Ok(v) => v,
+            Err(e) => e.reclaimed,
+        }
+    }
+
+    /// Like `sync()`, but give up after `timeout` has elapsed
+    ///
+    /// See [`Writer::sync_timeout()`].
+    pub fn sync_timeout(&self, timeout: Duration) -> Result<Vec<Box<T>>, SyncTimeout<T>> {
+        self.sync_until(Instant::now().checked_add(timeout))
+    }
+
+    /// Like `sync()`, but give up once `deadline` has passed
+    ///
+    /// See [`Writer::sync_deadline()`].
+    pub fn sync_deadline(&self, deadline: Instant) -> Result<Vec<Box<T>>, SyncTimeout<T>> {
+        self.sync_until(Some(deadline))
+    }
+
+    fn sync_until(&self, deadline: Option<Instant>) -> Result<Vec<Box<T>>, SyncTimeout<T>> {
+        if let Some(reclaimer) = &self.inner.reclaimer {
+            let mut v = Vec::new();
+            let done = |prevs: &Prevs<T>| prevs.is_empty();
+            return match reclaimer.sync_until(&self.inner.shared, deadline, &mut v, done) {
+                Ok(()) => Ok(v),
+                Err(o) => Err(o.into_timeout(v)),
+            };
+        }
+
+        // Holding `reclaim` ensures we're the only thread in `Shared::sync_until()`.
+        let mut reclaim = self.inner.reclaim.lock().unwrap();
+        let Reclaim { prevs, retire } = &mut *reclaim;
+
+        let mut v = Vec::new();
+        self.take_retired(prevs);
+        let r = self.inner.shared.sync_until(
+            prevs,
+            deadline,
+            retire_or_push(retire, &mut v),
+            |prevs| prevs.is_empty(),
+        );
+        self.inner
+            .reclaim_pending
+            .store(prevs.len(), atomic::Ordering::Relaxed);
+        match r {
+            Ok(()) => Ok(v),
+            Err(o) => Err(o.into_timeout(v)),
+        }
+    }
+
+    /// Move the values replaced since the last scan into `prevs`, handing the spare allocations
+    /// left over from previous scans to the next `write_nosync()`
+    fn take_retired(&self, prevs: &mut Prevs<T>) {
+        let mut retired = self.inner.retired.lock().unwrap();
+        prevs.append(&mut retired);
+        retired.take_spares(prevs);
+    }
+}
@@ -0,0 +1,316 @@
+//! Reporting readers that keep old values in use for too long, see [`StallDetector`]
+use crate::{Epochs, Prevs};
+use std::{
+    backtrace::Backtrace,
+    fmt, ptr,
+    sync::{
+        atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+type ReportFn = Box<dyn FnMut(&Stall) + Send>;
+
+/// Reports old values that stay in use by readers for longer than a threshold
+///
+/// Installed with [`crate::Builder::stall_detector()`]. Whenever old values are scanned for (by
+/// `write()`, `try_sync()`, `sync()`, a [`crate::Reclaimer`], ...), each old value that has been
+/// waiting for longer than the threshold is reported along with the readers that are still using
+/// it, and again each time another threshold passes. `sync()` wakes up at least once per
+/// threshold to check.
+///
+/// Readers can be given a name with [`crate::Reader::set_name()`], and the detector can capture a
+/// backtrace when each reader is created. By default, reports are printed to stderr.
+pub struct StallDetector {
+    pub(crate) threshold: Duration,
+    pub(crate) backtraces: bool,
+    report: Mutex<ReportFn>,
+}
+
+impl StallDetector {
+    /// Report old values that are still in use `threshold` after they were replaced
+    pub fn new(threshold: Duration) -> StallDetector {
+        StallDetector {
+            threshold,
+            backtraces: false,
+            report: Mutex::new(Box::new(|stall: &Stall| eprintln!("{stall}"))),
+        }
+    }
+
+    /// Capture a backtrace when each reader is created, to include in reports
+    ///
+    /// Capturing backtraces is slow, takes a lock in the standard library (so creating readers is
+    /// no longer lock free), and happens regardless of `RUST_BACKTRACE`.
+    pub fn backtraces(mut self, backtraces: bool) -> StallDetector {
+        self.backtraces = backtraces;
+        self
+    }
+
+    /// Pass reports to `report` instead of printing them to stderr
+    pub fn report<F: FnMut(&Stall) + Send + 'static>(self, report: F) -> StallDetector {
+        StallDetector {
+            report: Mutex::new(Box::new(report)),
+            ..self
+        }
+    }
+
+    /// Report each value in `prevs` that is overdue
+    ///
+    /// `prevs` must already have been scanned, so only readers still using each value remain.
+    pub(crate) fn check<T>(&self, prevs: &mut Prevs<T>, epochs: &Epochs) {
+        if prevs.is_empty() {
+            return;
+        }
+
+        let now = Instant::now();
+        for batch in prevs.batches_mut() {
+            for retired in &mut batch.values {
+                let Some(retired_at) = retired.retired_at else {
+                    continue;
+                };
+                let last = retired.last_report.unwrap_or(retired_at);
+                if now.saturating_duration_since(last) < self.threshold {
+                    continue;
+                }
+                retired.last_report = Some(now);
+
+                let stall = Stall {
+                    version: retired.node.version,
+                    pending_for: now.saturating_duration_since(retired_at),
+                    readers: batch
+                        .readers
+                        .iter()
+                        .map(|holdout| epochs.get(holdout.index).info.to_stalled())
+                        .collect(),
+                };
+                (self.report.lock().unwrap())(&stall);
+            }
+        }
+    }
+}
+
+/// Identifies a reader in stall reports
+///
+/// Lock free: the reader owning the slot replaces `info` with a swap, while inspecting it (for
+/// a report or `Writer::readers()`) is counted in `inspecting`. An `Info` replaced during an
+/// inspection is pushed to `garbage`, and freed once no inspection is in progress.
+pub(crate) struct ReaderInfo {
+    /// Null if the reader has neither a name nor a backtrace.
+    info: AtomicPtr<Info>,
+    /// Number of inspections of `info` in progress.
+    inspecting: AtomicUsize,
+    /// Replaced `Info`s that may still be inspected, linked through `Info::next`.
+    garbage: AtomicPtr<Info>,
+    /// Set if the reader was dropped in a read section, which can only happen if a guard was
+    /// leaked.
+    leaked: AtomicBool,
+}
+
+struct Info {
+    name: Option<String>,
+    backtrace: Option<Arc<Backtrace>>,
+    next: *mut Info,
+}
+
+impl ReaderInfo {
+    pub(crate) fn new() -> ReaderInfo {
+        ReaderInfo {
+            info: AtomicPtr::new(ptr::null_mut()),
+            inspecting: AtomicUsize::new(0),
+            garbage: AtomicPtr::new(ptr::null_mut()),
+            leaked: AtomicBool::new(false),
+        }
+    }
+
+    /// Forget the previous reader using this epoch slot, and capture a backtrace for the new one
+    /// if `stall` wants one
+    pub(crate) fn reset(&self, stall: Option<&StallDetector>) {
+        let backtrace = stall
+            .filter(|stall| stall.backtraces)
+            .map(|_| Arc::new(Backtrace::force_capture()));
+        // Nothing to forget unless the previous reader set a name or captured a backtrace.
+        if backtrace.is_some() || !self.info.load(Ordering::Relaxed).is_null() {
+            self.replace(None, backtrace);
+        }
+        self.leaked.store(false, Ordering::Relaxed);
+    }
+
+    /// Must only be called by the reader owning the slot
+    pub(crate) fn set_name(&self, name: String) {
+        let info = self.info.load(Ordering::Relaxed);
+        // SAFETY: only the owner replaces `info`, so it can't be freed under us.
+        let backtrace = unsafe { info.as_ref() }.and_then(|info| info.backtrace.clone());
+        self.replace(Some(name), backtrace);
+    }
+
+    pub(crate) fn name(&self) -> Option<String> {
+        self.inspect(|info| info.and_then(|info| info.name.clone()))
+    }
+
+    pub(crate) fn set_leaked(&self) {
+        self.leaked.store(true, Ordering::Relaxed);
+    }
+
+    fn to_stalled(&self) -> StalledReader {
+        let (name, backtrace) = self.inspect(|info| {
+            info.map_or((None, None), |info| {
+                (info.name.clone(), info.backtrace.clone())
+            })
+        });
+        StalledReader {
+            name,
+            backtrace,
+            leaked: self.leaked.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Swap in a new `Info`, freeing the old one once nobody can be inspecting it
+    fn replace(&self, name: Option<String>, backtrace: Option<Arc<Backtrace>>) {
+        let new = if name.is_none() && backtrace.is_none() {
+            ptr::null_mut()
+        } else {
+            Box::into_raw(Box::new(Info {
+                name,
+                backtrace,
+                next: ptr::null_mut(),
+            }))
+        };
+        // `SeqCst` here and in `inspect()`: either an inspection counted itself before we check
+        // `inspecting`, or it loads `info` after the swap and never sees `old`.
+        let old = self.info.swap(new, Ordering::SeqCst);
+        if old.is_null() {
+            return;
+        }
+        if self.inspecting.load(Ordering::SeqCst) == 0 {
+            // SAFETY: created by `Box::into_raw()` above, and no longer reachable.
+            drop(unsafe { Box::from_raw(old) });
+        } else {
+            self.push_garbage(old, old);
+            // The inspection may have finished before the push, so it didn't free `old`.
+            if self.inspecting.load(Ordering::SeqCst) == 0 {
+                self.collect();
+            }
+        }
+    }
+
+    fn inspect<R>(&self, f: impl FnOnce(Option<&Info>) -> R) -> R {
+        self.inspecting.fetch_add(1, Ordering::SeqCst);
+        // SAFETY: `replace()` doesn't free an `Info` while an inspection is counted.
+        let r = f(unsafe { self.info.load(Ordering::SeqCst).as_ref() });
+        if self.inspecting.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.collect();
+        }
+        r
+    }
+
+    /// Push the list from `head` to `tail` onto `garbage`
+    fn push_garbage(&self, head: *mut Info, tail: *mut Info) {
+        let mut top = self.garbage.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: the list is ours until it is pushed.
+            unsafe { (*tail).next = top };
+            match self
+                .garbage
+                .compare_exchange_weak(top, head, Ordering::SeqCst, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(actual) => top = actual,
+            }
+        }
+    }
+
+    /// Free the `garbage` if no inspection is in progress
+    ///
+    /// Anything pushed after this is freed by the next inspection, or when the slot is dropped.
+    fn collect(&self) {
+        let head = self.garbage.swap(ptr::null_mut(), Ordering::SeqCst);
+        if head.is_null() {
+            return;
+        }
+        if self.inspecting.load(Ordering::SeqCst) == 0 {
+            // SAFETY: every `Info` in `garbage` was unlinked from `info` before being pushed, and
+            // no inspection that could have loaded it is still in progress.
+            unsafe { free_list(head) };
+        } else {
+            let mut tail = head;
+            // SAFETY: we took the list, so nobody else is accessing it.
+            while let Some(next) = unsafe { (*tail).next.as_mut() } {
+                tail = next;
+            }
+            self.push_garbage(head, tail);
+        }
+    }
+}
+
+impl Drop for ReaderInfo {
+    fn drop(&mut self) {
+        let info = *self.info.get_mut();
+        if !info.is_null() {
+            // SAFETY: created by `Box::into_raw()`, and nobody else can access it any more.
+            drop(unsafe { Box::from_raw(info) });
+        }
+        // SAFETY: as above.
+        unsafe { free_list(*self.garbage.get_mut()) };
+    }
+}
+
+/// Free each `Info` in the list starting at `head`
+///
+/// # Safety
+///
+/// Each `Info` must have been created by `Box::into_raw()`, and not be accessed again.
+unsafe fn free_list(mut head: *mut Info) {
+    while !head.is_null() {
+        let info = Box::from_raw(head);
+        head = info.next;
+    }
+}
+
+/// An old value that has been in use by readers for longer than the [`StallDetector`] threshold
+#[derive(Debug)]
+pub struct Stall {
+    /// Version of the old value
+    pub version: u64,
+    /// How long ago the value was replaced
+    pub pending_for: Duration,
+    /// The readers still using the value
+    pub readers: Vec<StalledReader>,
+}
+
+/// A reader that is keeping an old value in use, see [`Stall`]
+#[derive(Debug, Clone)]
+pub struct StalledReader {
+    /// Set with [`crate::Reader::set_name()`]
+    pub name: Option<String>,
+    /// Where the reader was created, if [`StallDetector::backtraces()`] is enabled
+    pub backtrace: Option<Arc<Backtrace>>,
+    /// The reader was dropped while in a read section, which means a `ReadGuard` (or similar) was
+    /// leaked with `mem::forget()`. The value will never be collected.
+    pub leaked: bool,
+}
+
+impl fmt::Display for Stall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "local_rcu: value version {} still in use {:?} after it was replaced, by {} reader(s):",
+            self.version,
+            self.pending_for,
+            self.readers.len()
+        )?;
+        for reader in &self.readers {
+            write!(f, "\n  reader")?;
+            if let Some(name) = &reader.name {
+                write!(f, " {name:?}")?;
+            }
+            if reader.leaked {
+                write!(f, " (dropped while in a read section, a guard was leaked)")?;
+            }
+            if let Some(backtrace) = &reader.backtrace {
+                write!(f, ", created at:\n{backtrace}")?;
+            }
+        }
+        Ok(())
+    }
+}
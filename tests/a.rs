@@ -3,7 +3,7 @@ use std::thread;
 #[test]
 fn send_100_from_1_to_1() {
     let n = 100usize;
-    let (mut tx, mut rx) = local_rcu::slot(0usize);
+    let (mut tx, rx) = local_rcu::slot(0usize);
 
     let tx = thread::Builder::new()
         .name("producer".to_owned())
@@ -44,7 +44,7 @@ fn send_100_from_1_to_m() {
 
     let mut rx_t = Vec::with_capacity(m);
     for i in 0..m {
-        let mut rx = rx.clone();
+        let rx = rx.clone();
         rx_t.push(
             thread::Builder::new()
                 .name(format!("consumer {i} of {m}"))
@@ -0,0 +1,105 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+/// Counts the allocations made by each thread
+struct Counting;
+
+thread_local! {
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCS.try_with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocs() -> usize {
+    ALLOCS.with(|a| a.get())
+}
+
+#[test]
+fn alloc_free_write_into() {
+    let (mut w, r) = local_rcu::slot(0usize);
+    let mut old = Vec::with_capacity(2);
+    let mut write = |w: &mut local_rcu::Writer<usize>, val| {
+        // The reader is in a read section while the value is replaced, so it is collected by the
+        // next write.
+        let g = r.read();
+        w.write_into(val, &mut old);
+        drop(g);
+        old.clear();
+    };
+
+    for i in 0..10 {
+        write(&mut w, Box::new(i));
+    }
+
+    let vals: Vec<_> = (10..110).map(Box::new).collect();
+    let before = allocs();
+    for val in vals {
+        write(&mut w, val);
+    }
+    assert_eq!(allocs(), before);
+}
+
+#[test]
+fn alloc_free_try_sync_drain() {
+    let (mut w, r) = local_rcu::slot(0usize);
+    let write = |w: &mut local_rcu::Writer<usize>, val: Box<usize>| {
+        let expected = *val - 1;
+        let g = r.read();
+        w.write_nosync(val);
+        drop(g);
+        let mut drain = w.try_sync_drain();
+        assert_eq!(drain.len(), 1);
+        assert_eq!(drain.next().as_deref(), Some(&expected));
+    };
+
+    for i in 1..10 {
+        write(&mut w, Box::new(i));
+    }
+
+    let vals: Vec<_> = (10..110).map(Box::new).collect();
+    let before = allocs();
+    for val in vals {
+        write(&mut w, val);
+    }
+    assert_eq!(allocs(), before);
+}
+
+#[test]
+fn alloc_free_shared_writer() {
+    let (w, r) = local_rcu::Builder::new()
+        .retire(|val: Box<usize>| drop(val))
+        .slot(0usize);
+    let w = w.into_shared();
+    let write = |w: &local_rcu::SharedWriter<usize>, val| {
+        let g = r.read();
+        w.write_nosync(val);
+        drop(g);
+        // Old values are passed to the `Retire`, so the returned `Vec` is never allocated.
+        assert!(w.try_sync().is_empty());
+    };
+
+    for i in 0..10 {
+        write(&w, Box::new(i));
+    }
+
+    let vals: Vec<_> = (10..110).map(Box::new).collect();
+    let before = allocs();
+    for val in vals {
+        write(&w, val);
+    }
+    assert_eq!(allocs(), before);
+}
@@ -0,0 +1,61 @@
+#[test]
+fn batch_slow_reader() {
+    let (mut w, slow) = local_rcu::slot(0usize);
+    let fast = w.reader();
+
+    let g = slow.read();
+    for i in 1..=100 {
+        assert_eq!(*fast.read(), i - 1);
+        assert!(w.write(Box::new(i)).is_empty());
+    }
+    assert_eq!(w.pending().count(), 100);
+    assert!(w.pending().all(|p| p.readers == 1));
+
+    drop(g);
+    let old: Vec<_> = w.try_sync().into_iter().map(|v| *v).collect();
+    assert_eq!(old, (0..100).collect::<Vec<_>>());
+    assert!(!w.has_old_values());
+}
+
+#[test]
+fn batch_newer_reader_not_merged() {
+    let (mut w, r1) = local_rcu::slot(0usize);
+    let r2 = w.reader();
+
+    let g1 = r1.read();
+    w.write_nosync(Box::new(1));
+    let g2 = r2.read();
+    w.write_nosync(Box::new(2));
+
+    // 1 is still in use by `r2`.
+    drop(g1);
+    let old: Vec<_> = w.try_sync().into_iter().map(|v| *v).collect();
+    assert_eq!(old, [0]);
+
+    drop(g2);
+    let old: Vec<_> = w.try_sync().into_iter().map(|v| *v).collect();
+    assert_eq!(old, [1]);
+}
+
+#[test]
+fn batch_merged_after_scan() {
+    let (mut w, r1) = local_rcu::slot(0usize);
+    let r2 = w.reader();
+
+    let g1 = r1.read();
+    let g2 = r2.read();
+    w.write_nosync(Box::new(1));
+    drop(g2);
+    let g2 = r2.read();
+    w.write_nosync(Box::new(2));
+    drop(g2);
+
+    // Both values are now only waiting on `r1`.
+    assert!(w.try_sync().is_empty());
+    let pending: Vec<_> = w.pending().map(|p| (p.version, p.readers)).collect();
+    assert_eq!(pending, [(0, 1), (1, 1)]);
+
+    drop(g1);
+    let old: Vec<_> = w.try_sync().into_iter().map(|v| *v).collect();
+    assert_eq!(old, [0, 1]);
+}
@@ -0,0 +1,80 @@
+use std::{
+    future::Future,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake},
+    thread,
+};
+
+struct ThreadWaker(thread::Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+// Minimal executor so we don't need to pull one in as a dev-dependency
+fn block_on<F: Future>(f: F) -> F::Output {
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    let mut f = pin!(f);
+    loop {
+        if let Poll::Ready(v) = f.as_mut().poll(&mut cx) {
+            return v;
+        }
+        thread::park();
+    }
+}
+
+#[test]
+fn changed_wakes_on_write() {
+    let n = 100usize;
+    let (mut tx, rx) = local_rcu::slot(0usize);
+
+    let rx_t = thread::spawn(move || {
+        let mut prev = *rx.read();
+        while prev != n {
+            block_on(rx.changed()).unwrap();
+            let i = *rx.read();
+            assert!(i > prev, "{} <= {}", i, prev);
+            prev = i;
+        }
+    });
+
+    for i in 1..=n {
+        tx.write(Box::new(i));
+    }
+
+    rx_t.join().unwrap();
+}
+
+#[test]
+fn changed_ready_if_already_written() {
+    let (mut tx, rx) = local_rcu::slot(0usize);
+    tx.write(Box::new(1));
+    block_on(rx.changed()).unwrap();
+    assert_eq!(*rx.read(), 1);
+}
+
+#[test]
+fn changed_closed_on_writer_drop() {
+    let (tx, rx) = local_rcu::slot(0usize);
+
+    let rx_t = thread::spawn(move || block_on(rx.changed()));
+    drop(tx);
+
+    assert_eq!(rx_t.join().unwrap(), Err(local_rcu::Closed));
+}
+
+fn assert_send<F: Send>(_: &F) {}
+
+#[test]
+fn changed_is_send() {
+    let (_w, r) = local_rcu::slot(0usize);
+    let f = async move {
+        r.changed().await.unwrap();
+        *r.read()
+    };
+    assert_send(&f);
+}
@@ -0,0 +1,22 @@
+#[test]
+fn many_readers_reuse_slots() {
+    let (mut w, r) = local_rcu::slot(0usize);
+    let readers: Vec<_> = (0..100).map(|_| w.reader()).collect();
+    assert_eq!(w.reader_count(), 101);
+
+    // Hold a read section in readers spread across several chunks.
+    let guards: Vec<_> = readers.iter().step_by(30).map(|r| r.read()).collect();
+    assert!(w.write(Box::new(1)).is_empty());
+    assert_eq!(w.pending().next().unwrap().readers, guards.len());
+
+    drop(guards);
+    drop(readers);
+    assert_eq!(w.reader_count(), 1);
+
+    // New readers take over the old slots, without inheriting their state.
+    let readers: Vec<_> = (0..50).map(|_| w.reader()).collect();
+    assert!(w.readers().all(|s| s.reads == 0 && !s.in_read_section));
+    let _g = readers[0].read();
+    assert_eq!(w.try_sync().len(), 1);
+    drop(r);
+}
@@ -0,0 +1,65 @@
+#[test]
+fn inspect_readers_and_pending() {
+    let (mut w, r1) = local_rcu::slot(0usize);
+    let r2 = w.reader();
+    r2.set_name("second");
+    assert_eq!(w.reader_count(), 2);
+
+    drop(r1.read());
+    let g = r2.read();
+    let mut readers: Vec<_> = w.readers().collect();
+    readers.sort_by_key(|r| r.reads);
+    assert_eq!(
+        readers,
+        [
+            local_rcu::ReaderStatus {
+                name: None,
+                in_read_section: false,
+                reads: 1,
+            },
+            local_rcu::ReaderStatus {
+                name: Some("second".into()),
+                in_read_section: true,
+                reads: 1,
+            },
+        ]
+    );
+
+    w.write_nosync(Box::new(1));
+    w.write_nosync(Box::new(2));
+    let pending: Vec<_> = w
+        .pending()
+        .map(|p| (*p.value, p.version, p.readers))
+        .collect();
+    assert_eq!(pending, [(0, 0, 1), (1, 1, 1)]);
+
+    drop(g);
+    let pending: Vec<_> = w.pending().map(|p| p.readers).collect();
+    assert_eq!(pending, [0, 0]);
+    assert_eq!(w.try_sync().len(), 2);
+    assert_eq!(w.pending().count(), 0);
+
+    drop(r1);
+    assert_eq!(w.reader_count(), 1);
+}
+
+#[test]
+fn rename_while_inspecting() {
+    let (w, r) = local_rcu::slot(0usize);
+    std::thread::scope(|s| {
+        s.spawn(move || {
+            for i in 0..1000 {
+                r.set_name(format!("reader {i}"));
+            }
+        });
+        while w.reader_count() == 1 {
+            for status in w.readers() {
+                assert!(status.name.is_none_or(|name| name.starts_with("reader ")));
+            }
+        }
+    });
+
+    // A new reader taking over the slot doesn't inherit the name.
+    let _r = w.reader();
+    assert_eq!(w.readers().next().unwrap().name, None);
+}
@@ -0,0 +1,40 @@
+use std::{thread, time::Duration};
+
+#[test]
+fn observations_record_reader_latency() {
+    let (mut w, r1) = local_rcu::Builder::new().track_observations(2).slot(0usize);
+    let r2 = w.reader();
+
+    w.write(Box::new(1));
+    let g = r1.read();
+    assert_eq!(g.version(), 1);
+    assert!(g.published_at().elapsed() < Duration::from_secs(60));
+    drop(g);
+    thread::sleep(Duration::from_millis(5));
+    drop(r2.read());
+    // Reading the same version again isn't another observation.
+    drop(r1.read());
+
+    let o: Vec<_> = w.observations().collect();
+    assert_eq!(o.len(), 2);
+    assert_eq!(o[0].version, 1);
+    assert_eq!(o[0].readers, 2);
+    assert!(o[0].first.unwrap() < o[0].last.unwrap());
+    assert!(o[0].last.unwrap() >= Duration::from_millis(5));
+    // Both readers were created with the initial value already seen.
+    assert_eq!((o[1].version, o[1].readers, o[1].last), (0, 0, None));
+
+    w.write(Box::new(2));
+    w.write(Box::new(3));
+    let versions: Vec<_> = w.observations().map(|o| o.version).collect();
+    assert_eq!(versions, [3, 2]);
+    assert!(w.observations().all(|o| o.readers == 0));
+}
+
+#[test]
+fn observations_disabled_by_default() {
+    let (mut w, r) = local_rcu::slot(0usize);
+    w.write(Box::new(1));
+    drop(r.read());
+    assert_eq!(w.observations().count(), 0);
+}
@@ -0,0 +1,82 @@
+use local_rcu::Absorb;
+use std::collections::HashMap;
+
+#[derive(Clone, Default)]
+struct Map(HashMap<u32, u32>);
+
+enum Op {
+    Insert(u32, u32),
+    Remove(u32),
+}
+
+impl Absorb<Op> for Map {
+    fn absorb_first(&mut self, op: &mut Op, _other: &Self) {
+        match *op {
+            Op::Insert(k, v) => {
+                self.0.insert(k, v);
+            }
+            Op::Remove(k) => {
+                self.0.remove(&k);
+            }
+        }
+    }
+}
+
+#[test]
+fn left_right_applies_ops_to_both_copies() {
+    let (mut w, r) = local_rcu::left_right(Map::default());
+
+    w.append(Op::Insert(1, 1));
+    w.append(Op::Insert(2, 2));
+    assert!(w.has_pending());
+    assert!(r.read().0.is_empty());
+
+    w.publish();
+    assert!(!w.has_pending());
+    assert_eq!(w.version(), 1);
+    assert_eq!(r.read().0, HashMap::from([(1, 1), (2, 2)]));
+
+    w.append(Op::Remove(1));
+    w.publish();
+    assert_eq!(r.read().0, HashMap::from([(2, 2)]));
+
+    w.append(Op::Insert(3, 3));
+    w.publish();
+    assert_eq!(r.read().0, HashMap::from([(2, 2), (3, 3)]));
+    assert_eq!(w.read().0, HashMap::from([(2, 2), (3, 3)]));
+
+    // Nothing to publish, readers aren't notified.
+    w.publish();
+    assert!(w.try_publish());
+    assert_eq!(w.version(), 3);
+    assert!(!r.has_changed());
+
+    // The other copy catches up on `Insert(3, 3)` before absorbing new operations.
+    w.append(Op::Remove(2));
+    w.publish();
+    assert_eq!(r.read().0, HashMap::from([(3, 3)]));
+    w.append(Op::Insert(4, 4));
+    w.publish();
+    assert_eq!(r.read().0, HashMap::from([(3, 3), (4, 4)]));
+}
+
+#[test]
+fn try_publish_waits_for_readers() {
+    let (mut w, r) = local_rcu::left_right(Map::default());
+
+    w.append(Op::Insert(1, 1));
+    assert!(w.try_publish());
+    let g = r.read();
+    w.append(Op::Insert(2, 2));
+    assert!(w.try_publish());
+
+    // `g` is still using the other copy, so there is nowhere to apply the next operation.
+    w.append(Op::Insert(3, 3));
+    assert!(!w.try_publish());
+    assert!(w.has_pending());
+    assert_eq!(g.0, HashMap::from([(1, 1)]));
+    drop(g);
+
+    assert!(w.try_publish());
+    assert_eq!(r.read().0, HashMap::from([(1, 1), (2, 2), (3, 3)]));
+}
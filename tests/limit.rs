@@ -0,0 +1,91 @@
+use std::{sync::mpsc, thread, time::Duration};
+
+#[test]
+fn try_write_respects_max_pending() {
+    let (mut w, r) = local_rcu::Builder::new().max_pending(2).slot(0usize);
+
+    let g = r.read();
+    assert!(w.try_write(Box::new(1)).unwrap().is_empty());
+    assert!(w.try_write(Box::new(2)).unwrap().is_empty());
+    let e = w.try_write(Box::new(3)).unwrap_err();
+    assert_eq!(*e.value, 3);
+    assert!(e.reclaimed.is_empty());
+    assert_eq!(e.pending, 2);
+    assert_eq!(*w.read(), 2);
+
+    drop(g);
+    let old = w.try_write(e.value).unwrap();
+    assert_eq!(old.iter().map(|v| **v).collect::<Vec<_>>(), [0, 1, 2]);
+    assert_eq!(*w.read(), 3);
+}
+
+#[test]
+fn write_bounded_waits_by_weight() {
+    let (mut w, r) = local_rcu::Builder::new()
+        .max_pending_weight(6, |v: &Vec<u8>| v.len())
+        .slot(vec![0u8; 4]);
+    let (held_tx, held_rx) = mpsc::channel();
+
+    let r_t = thread::spawn(move || {
+        let g = r.read();
+        held_tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(g.len(), 4);
+    });
+
+    held_rx.recv().unwrap();
+    w.write_nosync(Box::new(vec![1; 4]));
+    // 4 pending, replacing the current value would make it 8.
+    assert_eq!(w.try_write(Box::new(vec![2; 4])).unwrap_err().pending, 4);
+
+    let old = w.write_bounded(Box::new(vec![2; 4]));
+    assert_eq!(old.iter().map(|v| v[0]).collect::<Vec<_>>(), [0, 1]);
+    assert_eq!(w.read()[0], 2);
+
+    r_t.join().unwrap();
+}
+
+#[test]
+fn shared_writer_respects_max_pending() {
+    let (w, r) = local_rcu::Builder::new().max_pending(2).slot(0usize);
+    let w = w.into_shared();
+    let (held_tx, held_rx) = mpsc::channel();
+
+    let r_t = thread::spawn(move || {
+        let g = r.read();
+        held_tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(*g, 0);
+    });
+
+    held_rx.recv().unwrap();
+    let w2 = w.clone();
+    thread::spawn(move || w2.write_nosync(Box::new(1)))
+        .join()
+        .unwrap();
+    assert!(w.try_write(Box::new(2)).unwrap().is_empty());
+    let e = w.try_write(Box::new(3)).unwrap_err();
+    assert_eq!(*e.value, 3);
+    assert_eq!(e.pending, 2);
+
+    let old = w.write_bounded(e.value);
+    assert_eq!(old.iter().map(|v| **v).collect::<Vec<_>>(), [0, 1, 2]);
+    assert_eq!(w.version(), 3);
+
+    r_t.join().unwrap();
+}
+
+#[test]
+fn try_write_scans_once() {
+    let (mut w, r) = local_rcu::Builder::new()
+        .max_pending(1)
+        .metrics(true)
+        .slot(0usize);
+
+    let g = r.read();
+    assert!(w.try_write(Box::new(1)).unwrap().is_empty());
+    let scans = w.metrics().unwrap().scans;
+    assert!(w.try_write(Box::new(2)).is_err());
+    assert_eq!(w.metrics().unwrap().scans, scans + 1);
+    drop(g);
+}
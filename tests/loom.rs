@@ -12,7 +12,7 @@ fn loom_send_100_from_1_to_m() {
 
         let mut rx_t = Vec::with_capacity(m);
         for _ in 0..m {
-            let mut rx = rx.clone();
+            let rx = rx.clone();
             rx_t.push(thread::spawn(move || {
                 let mut prev = 0;
                 loop {
@@ -48,7 +48,7 @@ fn loom_send_100_from_1_to_m() {
 fn loom_send_from_1_to_1() {
     loom::model(|| {
         let n = 2usize;
-        let (mut tx, mut rx) = local_rcu::slot(0usize);
+        let (mut tx, rx) = local_rcu::slot(0usize);
 
         let rx_t = thread::spawn(move || {
             let mut prev = 0;
@@ -95,7 +95,7 @@ fn loom_send_from_1_to_1() {
 #[test]
 fn loom_send_1_from_1_to_1() {
     loom::model(|| {
-        let (mut tx, mut rx) = local_rcu::slot(0usize);
+        let (mut tx, rx) = local_rcu::slot(0usize);
 
         let rx_t = thread::spawn(move || loop {
             let i = *rx.read();
@@ -112,3 +112,174 @@ fn loom_send_1_from_1_to_1() {
         rx_t.join().unwrap();
     });
 }
+
+#[cfg(loom)]
+#[test]
+fn loom_changed_1_from_1_to_1() {
+    loom::model(|| {
+        let (mut tx, rx) = local_rcu::slot(0usize);
+
+        let rx_t = thread::spawn(move || {
+            loom::future::block_on(rx.changed()).unwrap();
+            assert_eq!(*rx.read(), 1);
+        });
+
+        tx.write(Box::new(1));
+
+        rx_t.join().unwrap();
+    });
+}
+
+#[cfg(loom)]
+#[test]
+fn loom_sync_parks_until_guard_drop() {
+    loom::model(|| {
+        let (mut tx, rx) = local_rcu::slot(0usize);
+
+        let rx_t = thread::spawn(move || {
+            let i = *rx.read();
+            assert!(i <= 1);
+        });
+
+        tx.write_nosync(Box::new(1));
+        let old = tx.sync();
+        assert_eq!(old.len(), 1);
+        assert!(!tx.has_old_values());
+
+        rx_t.join().unwrap();
+    });
+}
+
+#[cfg(loom)]
+#[test]
+fn loom_shared_writer_2_to_1() {
+    loom::model(|| {
+        let (w, rx) = local_rcu::mpmc_slot(0usize);
+
+        let w2 = w.clone();
+        let tx_t = thread::spawn(move || {
+            w2.write(Box::new(1));
+        });
+
+        let g = rx.read();
+        w.write(Box::new(2));
+        assert!(*g <= 1);
+        drop(g);
+
+        tx_t.join().unwrap();
+
+        w.sync();
+        assert!(!w.has_old_values());
+        assert_eq!(w.version(), 2);
+    });
+}
+
+#[cfg(loom)]
+#[test]
+fn loom_register_while_writing() {
+    loom::model(|| {
+        let (mut tx, rx) = local_rcu::slot(0usize);
+
+        let rx_t = thread::spawn(move || {
+            // The second reader reuses the first one's slot.
+            for _ in 0..2 {
+                let r = rx.clone();
+                let i = *r.read();
+                assert!(i <= 1, "unexpected {i}");
+            }
+        });
+
+        tx.write_nosync(Box::new(1));
+        for mut old in tx.sync() {
+            *old = 0xdeadbeef;
+        }
+
+        rx_t.join().unwrap();
+    });
+}
+
+#[cfg(loom)]
+#[test]
+fn loom_changed_2_waiters_write_nosync() {
+    // 3 threads takes way too long without a bound.
+    let mut model = loom::model::Builder::new();
+    model.preemption_bound = Some(2);
+    model.check(|| {
+        let (mut tx, rx) = local_rcu::slot(0usize);
+
+        // While one waiter holds the wakers lock, the writer must leave waking to it rather than
+        // block, without either waiter missing the new value.
+        let rx_t: Vec<_> = (0..2)
+            .map(|_| {
+                let rx = rx.clone();
+                thread::spawn(move || {
+                    loom::future::block_on(rx.changed()).unwrap();
+                    assert_eq!(*rx.read(), 1);
+                })
+            })
+            .collect();
+
+        tx.write_nosync(Box::new(1));
+
+        for rx_t in rx_t {
+            rx_t.join().unwrap();
+        }
+        drop(rx);
+        tx.sync();
+    });
+}
+
+#[cfg(loom)]
+#[test]
+fn loom_write_nosync_while_registering() {
+    loom::model(|| {
+        let (mut tx, rx) = local_rcu::slot(0usize);
+
+        let rx_t = thread::spawn(move || {
+            let r = rx.clone();
+            drop(rx);
+            let i = *r.read();
+            assert!(i <= 2, "unexpected {i}");
+        });
+
+        for i in 1..=2 {
+            tx.write_nosync(Box::new(i));
+            for mut old in tx.try_sync() {
+                *old = 0xdeadbeef;
+            }
+        }
+
+        rx_t.join().unwrap();
+        for mut old in tx.sync() {
+            *old = 0xdeadbeef;
+        }
+    });
+}
+
+#[cfg(loom)]
+#[test]
+fn loom_membarrier_send_from_1_to_1() {
+    // loom can't model `membarrier()`, so both the reader's compiler fence and the writer's
+    // `membarrier()` are modelled as `fence(SeqCst)`, the same as without it. This only checks that
+    // the writer issues a `membarrier()` wherever readers rely on one, not the asymmetric protocol.
+    loom::model(|| {
+        let (mut tx, rx) = local_rcu::Builder::new().membarrier(true).slot(0usize);
+        assert!(tx.uses_membarrier());
+
+        let rx_t = thread::spawn(move || {
+            // Reads both before & after the value is reclaimed.
+            for _ in 0..2 {
+                let i = *rx.read();
+                assert!(i <= 1, "unexpected {i}");
+                loom::thread::yield_now();
+            }
+        });
+
+        tx.write_nosync(Box::new(1));
+        for mut old in tx.sync() {
+            *old = 0xdeadbeef;
+        }
+
+        rx_t.join().unwrap();
+    });
+}
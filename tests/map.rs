@@ -0,0 +1,47 @@
+use local_rcu::{MappedReadGuard, ReadGuard};
+
+struct Config {
+    name: String,
+    limits: Vec<usize>,
+}
+
+#[test]
+fn map_holds_read_section() {
+    let (mut w, r) = local_rcu::slot(Config {
+        name: "a".to_owned(),
+        limits: vec![1, 2],
+    });
+
+    let name = ReadGuard::map(r.read(), |c| c.name.as_str());
+    w.write(Box::new(Config {
+        name: "b".to_owned(),
+        limits: vec![],
+    }));
+    assert!(w.has_old_values());
+    assert_eq!(&*name, "a");
+    assert_eq!(name.version(), 0);
+
+    drop(name);
+    assert_eq!(w.try_sync().len(), 1);
+    assert_eq!(&*ReadGuard::map(r.read(), |c| &c.name), "b");
+}
+
+#[test]
+fn try_map() {
+    let (_w, r) = local_rcu::slot(Config {
+        name: "a".to_owned(),
+        limits: vec![1, 2],
+    });
+
+    let Err(g) = ReadGuard::try_map(r.read(), |c| c.limits.get(5)) else {
+        panic!("limit should not exist");
+    };
+    assert_eq!(g.name, "a");
+    drop(g);
+
+    let limits = ReadGuard::map(r.read(), |c| &c.limits);
+    let Ok(second) = MappedReadGuard::try_map(limits, |l| l.get(1)) else {
+        panic!("limit should exist");
+    };
+    assert_eq!(*second, 2);
+}
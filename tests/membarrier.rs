@@ -0,0 +1,85 @@
+use std::{sync::mpsc, thread};
+
+/// Does the kernel support `MEMBARRIER_CMD_PRIVATE_EXPEDITED`?
+#[cfg(target_os = "linux")]
+fn membarrier_supported() -> bool {
+    // SAFETY: `MEMBARRIER_CMD_QUERY` doesn't access memory.
+    let supported = unsafe {
+        libc::syscall(
+            libc::SYS_membarrier,
+            libc::MEMBARRIER_CMD_QUERY,
+            0 as libc::c_uint,
+            0 as libc::c_int,
+        )
+    };
+    supported >= 0 && supported & libc::MEMBARRIER_CMD_PRIVATE_EXPEDITED as libc::c_long != 0
+}
+
+#[cfg(not(target_os = "linux"))]
+fn membarrier_supported() -> bool {
+    false
+}
+
+#[test]
+fn membarrier_used_when_enabled_and_supported() {
+    let (w, _r) = local_rcu::Builder::new().membarrier(true).slot(0usize);
+    assert_eq!(w.uses_membarrier(), membarrier_supported());
+
+    let (w, _r) = local_rcu::slot(0usize);
+    assert!(!w.uses_membarrier());
+}
+
+#[test]
+fn membarrier_readers_see_writes() {
+    let n = 1000usize;
+    let (mut w, r) = local_rcu::Builder::new().membarrier(true).slot(0usize);
+
+    let r_t: Vec<_> = (0..4)
+        .map(|_| {
+            let r = r.clone();
+            thread::spawn(move || {
+                let mut prev = 0;
+                loop {
+                    let i = *r.read();
+                    assert!(prev <= i, "{prev} > {i}");
+                    if i == n {
+                        break;
+                    }
+                    prev = i;
+                }
+            })
+        })
+        .collect();
+
+    for i in 1..=n {
+        for mut old in w.write(Box::new(i)) {
+            *old = usize::MAX;
+        }
+    }
+    for r_t in r_t {
+        r_t.join().unwrap();
+    }
+}
+
+#[test]
+fn membarrier_sync_waits_for_guard_drop() {
+    let (mut w, r) = local_rcu::Builder::new().membarrier(true).slot(0usize);
+    let (held_tx, held_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+
+    let r_t = thread::spawn(move || {
+        let g = r.read();
+        held_tx.send(()).unwrap();
+        release_rx.recv().unwrap();
+        assert_eq!(*g, 0);
+    });
+
+    held_rx.recv().unwrap();
+    w.write_nosync(Box::new(1));
+    assert!(w.try_sync().is_empty());
+    release_tx.send(()).unwrap();
+    let old = w.sync();
+    assert_eq!(old.iter().map(|v| **v).collect::<Vec<_>>(), [0]);
+
+    r_t.join().unwrap();
+}
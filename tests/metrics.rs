@@ -0,0 +1,68 @@
+use std::{thread, time::Duration};
+
+#[test]
+fn metrics_count_writes_and_reclaims() {
+    let (mut w, r) = local_rcu::Builder::new().metrics(true).slot(0usize);
+    let r2 = w.reader();
+
+    let g = r.read();
+    thread::sleep(Duration::from_millis(5));
+    w.write(Box::new(1));
+    w.write(Box::new(2));
+
+    let m = w.metrics().unwrap();
+    assert_eq!(m.writes, 2);
+    // Both old values were replaced while `g` was held.
+    assert_eq!(m.reclaimed, 0);
+    assert_eq!(m.pending, 2);
+    assert_eq!(m.scans, 2);
+    assert!(m.oldest_pending.is_some());
+    assert_eq!(m.readers, 2);
+    assert_eq!(m.readers_registered, 2);
+    assert_eq!(m.guard_holds, 0);
+
+    drop(g);
+    drop(r2);
+    assert_eq!(w.try_sync().len(), 2);
+
+    let m = w.metrics().unwrap();
+    assert_eq!(m.reclaimed, 2);
+    assert_eq!(m.pending, 0);
+    assert_eq!(m.oldest_pending, None);
+    assert_eq!(m.readers, 1);
+    assert_eq!(m.readers_deregistered, 1);
+    assert_eq!(m.guard_holds, 1);
+    assert!(m.guard_hold_max >= Duration::from_millis(5));
+    assert!(m.reclaim_lag_max > Duration::ZERO);
+
+    let text = m.to_prometheus("config \"main\"");
+    assert!(text.contains("# TYPE local_rcu_writes_total counter\n"));
+    assert!(text.contains("local_rcu_writes_total{slot=\"config \\\"main\\\"\"} 2\n"));
+    assert!(text.contains("local_rcu_pending{slot=\"config \\\"main\\\"\"} 0\n"));
+}
+
+#[test]
+fn prometheus_groups_slots_under_one_type_line() {
+    let (mut a, _ra) = local_rcu::Builder::new().metrics(true).slot(0usize);
+    let (mut b, _rb) = local_rcu::Builder::new().metrics(true).slot(0usize);
+    a.write(Box::new(1));
+    b.write(Box::new(1));
+    b.write(Box::new(2));
+    let (ma, mb) = (a.metrics().unwrap(), b.metrics().unwrap());
+
+    let mut text = String::new();
+    local_rcu::MetricsSnapshot::write_prometheus(&mut text, &[("a", &ma), ("b", &mb)]).unwrap();
+    assert_eq!(text.matches("local_rcu_writes_total counter").count(), 1);
+    assert_eq!(text.matches("# TYPE ").count(), 13);
+    assert!(text.contains(
+        "# TYPE local_rcu_writes_total counter\n\
+         local_rcu_writes_total{slot=\"a\"} 1\n\
+         local_rcu_writes_total{slot=\"b\"} 2\n"
+    ));
+}
+
+#[test]
+fn metrics_disabled_by_default() {
+    let (w, _r) = local_rcu::slot(0usize);
+    assert_eq!(w.metrics(), None);
+}
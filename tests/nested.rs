@@ -0,0 +1,29 @@
+use local_rcu::Reader;
+
+fn inner(r: &Reader<usize>) -> usize {
+    *r.read()
+}
+
+#[test]
+fn nested_reads_share_read_section() {
+    let (mut w, r) = local_rcu::slot(0usize);
+
+    let outer = r.read();
+    w.write(Box::new(1));
+
+    // nested read sees the newer value, and the older value is still held by `outer`
+    assert_eq!(inner(&r), 1);
+    assert_eq!(*outer, 0);
+
+    let nested = r.read();
+    w.write(Box::new(2));
+    assert!(w.try_sync().is_empty());
+
+    drop(outer);
+    // `nested` still holds the read section, so nothing is released
+    assert!(w.try_sync().is_empty());
+    assert_eq!(*nested, 1);
+
+    drop(nested);
+    assert_eq!(w.try_sync().len(), 2);
+}
@@ -0,0 +1,55 @@
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Default)]
+struct Log(Arc<Mutex<Vec<String>>>);
+
+impl local_rcu::RcuObserver for Log {
+    fn published(&self, version: u64) {
+        self.0.lock().unwrap().push(format!("published {version}"));
+    }
+
+    fn retired(&self, version: u64, readers: usize) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("retired {version} ({readers} readers)"));
+    }
+
+    fn reclaimed(&self, version: u64) {
+        self.0.lock().unwrap().push(format!("reclaimed {version}"));
+    }
+
+    fn reader_registered(&self) {
+        self.0.lock().unwrap().push("reader registered".into());
+    }
+
+    fn reader_dropped(&self) {
+        self.0.lock().unwrap().push("reader dropped".into());
+    }
+}
+
+#[test]
+fn observer_sees_lifecycle() {
+    let log = Log::default();
+    let (mut w, r) = local_rcu::Builder::new().observer(log.clone()).slot(0usize);
+
+    let g = r.read();
+    assert!(w.write(Box::new(1)).is_empty());
+    drop(g);
+    assert_eq!(w.write(Box::new(2)).len(), 2);
+    drop(r);
+
+    assert_eq!(
+        *log.0.lock().unwrap(),
+        [
+            "reader registered",
+            "published 1",
+            "retired 0 (1 readers)",
+            "published 2",
+            "retired 1 (0 readers)",
+            "reclaimed 0",
+            "reclaimed 1",
+            "reader dropped",
+        ]
+    );
+}
@@ -0,0 +1,33 @@
+use local_rcu::{OwnedReadGuard, Reader};
+use std::thread;
+
+struct Holder {
+    guard: OwnedReadGuard<String>,
+}
+
+fn open(r: Reader<String>) -> Holder {
+    Holder {
+        guard: r.read_owned(),
+    }
+}
+
+#[test]
+fn owned_guard_holds_read_section() {
+    let (mut w, r) = local_rcu::slot("a".to_owned());
+    let h = open(r);
+
+    w.write(Box::new("b".to_owned()));
+    assert!(w.has_old_values());
+
+    let h = thread::spawn(move || {
+        assert_eq!(*h.guard, "a");
+        assert_eq!(h.guard.version(), 0);
+        h
+    })
+    .join()
+    .unwrap();
+
+    let r = OwnedReadGuard::into_reader(h.guard);
+    assert_eq!(w.try_sync().len(), 1);
+    assert_eq!(*r.read(), "b");
+}
@@ -0,0 +1,52 @@
+use local_rcu::policy::{EveryNWrites, PendingThreshold};
+
+#[test]
+fn every_n_writes_scans_periodically() {
+    let (mut w, _r) = local_rcu::Builder::new()
+        .reclaim_policy(EveryNWrites::new(3))
+        .slot(0usize);
+
+    assert!(w.write(Box::new(1)).is_empty());
+    assert!(w.write(Box::new(2)).is_empty());
+    let old = w.write(Box::new(3));
+    assert_eq!(old.iter().map(|v| **v).collect::<Vec<_>>(), [0, 1, 2]);
+    assert!(!w.has_old_values());
+}
+
+#[test]
+fn pending_threshold_and_closure() {
+    let (mut w, r) = local_rcu::Builder::new()
+        .reclaim_policy(PendingThreshold::new(1))
+        .slot(0usize);
+
+    assert!(w.write(Box::new(1)).is_empty());
+    let g = r.read();
+    // `0` is collected once there are 2 pending, `1` is still in use.
+    let old = w.write(Box::new(2));
+    assert_eq!(old.iter().map(|v| **v).collect::<Vec<_>>(), [0]);
+    assert!(w.write(Box::new(3)).is_empty());
+    drop(g);
+    let old = w.write(Box::new(4));
+    assert_eq!(old.iter().map(|v| **v).collect::<Vec<_>>(), [1, 2, 3]);
+
+    let (mut w, _r) = local_rcu::Builder::new()
+        .reclaim_policy(|_pending| false)
+        .slot(0usize);
+    assert!(w.write(Box::new(1)).is_empty());
+    assert!(w.has_old_values());
+    assert_eq!(w.try_sync().len(), 1);
+}
+
+#[test]
+fn shared_writer_keeps_policy() {
+    let (w, _r) = local_rcu::Builder::new()
+        .reclaim_policy(EveryNWrites::new(3))
+        .slot(0usize);
+    let w = w.into_shared();
+
+    assert!(w.write(Box::new(1)).is_empty());
+    assert!(w.write(Box::new(2)).is_empty());
+    let old = w.write(Box::new(3));
+    assert_eq!(old.iter().map(|v| **v).collect::<Vec<_>>(), [0, 1, 2]);
+    assert!(!w.has_old_values());
+}
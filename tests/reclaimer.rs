@@ -0,0 +1,86 @@
+use std::{
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn reclaimer_retires_off_thread() {
+    let (tx, rx) = mpsc::channel();
+    let (mut w, r) = local_rcu::Builder::new()
+        .retire(move |v: Box<usize>| {
+            tx.send((*v, thread::current().name().map(String::from)))
+                .unwrap()
+        })
+        .reclaimer(
+            local_rcu::Reclaimer::new()
+                .interval(Duration::from_millis(1))
+                .name("reclaimer"),
+        )
+        .slot(0usize);
+
+    let g = r.read();
+    assert!(w.write(Box::new(1)).is_empty());
+    assert!(w.has_old_values());
+    assert!(rx.recv_timeout(Duration::from_millis(20)).is_err());
+
+    // Nothing else is written, the reclaimer's timer picks up `0` once the guard is dropped.
+    drop(g);
+    let (v, name) = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(v, 0);
+    assert_eq!(name.as_deref(), Some("reclaimer"));
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while w.has_old_values() {
+        assert!(Instant::now() < deadline);
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn reclaimer_sync_returns_values() {
+    let (mut w, r) = local_rcu::Builder::new()
+        .reclaimer(local_rcu::Reclaimer::new())
+        .slot(0usize);
+
+    let g = r.read();
+    w.write_nosync(Box::new(1));
+    assert!(w.try_sync().is_empty());
+    let e = w.sync_timeout(Duration::from_millis(10)).unwrap_err();
+    assert!(e.reclaimed.is_empty());
+    assert_eq!(e.pending, 1);
+
+    drop(g);
+    let old = w.sync();
+    assert_eq!(old.iter().map(|v| **v).collect::<Vec<_>>(), [0]);
+    assert!(!w.has_old_values());
+
+    // Old values still in use when the `Writer` is dropped are freed with the last `Reader`.
+    let _g = r.read();
+    w.into_shared().write_nosync(Box::new(2));
+}
+
+#[test]
+fn reclaimer_retires_without_blocking_writer() {
+    let (entered_tx, entered_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    let (mut w, _r) = local_rcu::Builder::new()
+        .retire(move |_: Box<usize>| {
+            entered_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        })
+        .reclaimer(local_rcu::Reclaimer::new())
+        .slot(0usize);
+
+    w.write(Box::new(1));
+    entered_rx.recv_timeout(Duration::from_secs(10)).unwrap();
+
+    // The reclaimer thread is stuck in the `Retire`, which must not hold up the writer.
+    let (done_tx, done_rx) = mpsc::channel();
+    thread::scope(|s| {
+        s.spawn(|| done_tx.send(w.has_old_values()).unwrap());
+        let done = done_rx.recv_timeout(Duration::from_secs(1));
+        release_tx.send(()).unwrap();
+        assert!(!done.unwrap());
+    });
+}
@@ -0,0 +1,45 @@
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn retire_receives_old_values() {
+    let retired = Arc::new(Mutex::new(Vec::new()));
+    let (mut w, r) = local_rcu::Builder::new()
+        .retire({
+            let retired = retired.clone();
+            move |v: Box<usize>| retired.lock().unwrap().push(*v)
+        })
+        .slot(0usize);
+
+    let g = r.read();
+    assert!(w.write(Box::new(1)).is_empty());
+    assert!(w.write(Box::new(2)).is_empty());
+    assert!(retired.lock().unwrap().is_empty());
+
+    drop(g);
+    assert!(w.try_sync().is_empty());
+    assert_eq!(*retired.lock().unwrap(), [0, 1]);
+
+    let _g = r.read();
+    w.write_nosync(Box::new(3));
+    let e = w
+        .sync_timeout(std::time::Duration::from_millis(1))
+        .unwrap_err();
+    assert!(e.reclaimed.is_empty());
+    assert_eq!(e.pending, 1);
+}
+
+#[test]
+fn shared_writer_keeps_retire() {
+    let retired = Arc::new(Mutex::new(Vec::new()));
+    let (w, _r) = local_rcu::Builder::new()
+        .retire({
+            let retired = retired.clone();
+            move |v: Box<usize>| retired.lock().unwrap().push(*v)
+        })
+        .slot(0usize);
+    let w = w.into_shared();
+
+    assert!(w.write(Box::new(1)).is_empty());
+    assert!(w.sync().is_empty());
+    assert_eq!(*retired.lock().unwrap(), [0]);
+}
@@ -0,0 +1,57 @@
+use std::thread;
+
+#[test]
+fn multiple_producers() {
+    let n = 100usize;
+    let m = 4usize;
+    let (w, r) = local_rcu::mpmc_slot((0usize, 0usize));
+
+    let mut producers = Vec::with_capacity(m);
+    for p in 0..m {
+        let w = w.clone();
+        producers.push(thread::spawn(move || {
+            let mut old = Vec::new();
+            for i in 1..=n {
+                old.extend(w.write(Box::new((p, i))));
+            }
+            old.len()
+        }));
+    }
+
+    let consumer = {
+        let r = r.clone();
+        thread::spawn(move || {
+            let mut prev = 0;
+            while prev != (n * m) as u64 {
+                let v = r.read().version();
+                assert!(v >= prev, "{} < {}", v, prev);
+                prev = v;
+            }
+        })
+    };
+
+    let mut collected: usize = producers.into_iter().map(|p| p.join().unwrap()).sum();
+    consumer.join().unwrap();
+    collected += w.sync().len();
+
+    // every value but the active one has been returned
+    assert_eq!(collected, n * m);
+    assert!(!w.has_old_values());
+    assert_eq!(w.version(), (n * m) as u64);
+
+    let g = r.read();
+    assert_eq!(g.version(), (n * m) as u64);
+    assert_eq!(g.1, n);
+}
+
+#[test]
+fn old_values_outlive_shared_writer() {
+    let (w, r) = local_rcu::mpmc_slot(vec![0usize]);
+    let g = r.read();
+    let w2 = w.clone();
+    w.write(Box::new(vec![1]));
+    w2.write(Box::new(vec![2]));
+    drop(w);
+    drop(w2);
+    assert_eq!(*g, [0]);
+}
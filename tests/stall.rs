@@ -0,0 +1,77 @@
+use local_rcu::StallDetector;
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn stall_reports_named_reader() {
+    let stalls = Arc::new(Mutex::new(Vec::new()));
+    let (mut w, r) = local_rcu::Builder::new()
+        .stall_detector(
+            StallDetector::new(Duration::from_millis(10))
+                .backtraces(true)
+                .report({
+                    let stalls = stalls.clone();
+                    move |stall: &local_rcu::Stall| {
+                        stalls
+                            .lock()
+                            .unwrap()
+                            .push((stall.version, stall.readers.clone()))
+                    }
+                }),
+        )
+        .slot(0usize);
+    r.set_name("slow");
+    let _r2 = w.reader();
+
+    let g = r.read();
+    w.write(Box::new(1));
+    assert!(w.try_sync().is_empty());
+    assert!(stalls.lock().unwrap().is_empty());
+
+    thread::sleep(Duration::from_millis(20));
+    assert!(w.try_sync().is_empty());
+    // Not reported again until another threshold has passed.
+    assert!(w.try_sync().is_empty());
+    drop(g);
+    assert_eq!(w.try_sync().len(), 1);
+
+    let (version, readers) = stalls.lock().unwrap().pop().unwrap();
+    assert!(stalls.lock().unwrap().is_empty());
+    assert_eq!(version, 0);
+    assert_eq!(readers.len(), 1);
+    assert_eq!(readers[0].name.as_deref(), Some("slow"));
+    assert!(!readers[0].leaked);
+
+    let msg = local_rcu::Stall {
+        version,
+        pending_for: Duration::from_millis(20),
+        readers,
+    }
+    .to_string();
+    assert!(msg.contains("version 0 still in use 20ms after it was replaced, by 1 reader(s)"));
+    assert!(msg.contains("reader \"slow\", created at:"));
+}
+
+#[test]
+fn stall_reports_leaked_guard_while_syncing() {
+    let stalls = Arc::new(Mutex::new(Vec::new()));
+    let (mut w, r) = local_rcu::Builder::new()
+        .stall_detector(StallDetector::new(Duration::from_millis(5)).report({
+            let stalls = stalls.clone();
+            move |stall: &local_rcu::Stall| stalls.lock().unwrap().push(stall.readers[0].leaked)
+        }))
+        .slot(0usize);
+
+    std::mem::forget(r.read());
+    w.write_nosync(Box::new(1));
+    drop(r);
+
+    let e = w.sync_timeout(Duration::from_millis(50)).unwrap_err();
+    assert_eq!(e.pending, 1);
+    let stalls = stalls.lock().unwrap();
+    assert!(stalls.len() >= 2);
+    assert!(stalls.iter().all(|leaked| *leaked));
+}
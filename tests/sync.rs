@@ -0,0 +1,59 @@
+use std::{
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn sync_waits_for_guard_drop() {
+    let (mut w, r) = local_rcu::slot(0usize);
+    let (held_tx, held_rx) = mpsc::channel();
+
+    let r_t = thread::spawn(move || {
+        let g = r.read();
+        held_tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(*g, 0);
+    });
+
+    held_rx.recv().unwrap();
+    w.write_nosync(Box::new(1));
+    let start = Instant::now();
+    let old = w.sync();
+    assert!(start.elapsed() > Duration::from_millis(10));
+    assert_eq!(old.iter().map(|v| **v).collect::<Vec<_>>(), [0]);
+    assert!(!w.has_old_values());
+
+    r_t.join().unwrap();
+}
+
+#[test]
+fn sync_timeout_reports_outstanding() {
+    let (mut w, r1) = local_rcu::slot(0usize);
+    let r2 = w.reader();
+
+    let g1 = r1.read();
+    w.write_nosync(Box::new(1));
+    w.write_nosync(Box::new(2));
+
+    let e = w.sync_timeout(Duration::from_millis(10)).unwrap_err();
+    assert!(e.reclaimed.is_empty());
+    assert_eq!(e.pending, 2);
+    assert_eq!(e.readers, 1);
+
+    let g2 = r2.read();
+    assert_eq!(*g2, 2);
+    w.write_nosync(Box::new(3));
+    drop(g1);
+
+    let e = w
+        .sync_deadline(Instant::now() + Duration::from_millis(10))
+        .unwrap_err();
+    assert_eq!(e.reclaimed.iter().map(|v| **v).collect::<Vec<_>>(), [0, 1]);
+    assert_eq!(e.pending, 1);
+    assert_eq!(e.readers, 1);
+
+    drop(g2);
+    let old = w.sync_timeout(Duration::from_millis(10)).unwrap();
+    assert_eq!(old.iter().map(|v| **v).collect::<Vec<_>>(), [2]);
+}
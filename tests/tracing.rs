@@ -0,0 +1,66 @@
+#![cfg(feature = "tracing")]
+use std::sync::{Arc, Mutex};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// Records each event as its message followed by its other fields
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{value:?}"));
+        } else {
+            self.0.push_str(&format!(" {}={value:?}", field.name()));
+        }
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "local_rcu"
+    }
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(String::new());
+        event.record(&mut fields);
+        self.0.lock().unwrap().push(fields.0);
+    }
+    fn enter(&self, _: &span::Id) {}
+    fn exit(&self, _: &span::Id) {}
+}
+
+#[test]
+fn tracing_events() {
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let (mut w, r) = local_rcu::slot(0usize);
+        let g = r.read();
+        w.write(Box::new(1));
+        drop(g);
+        assert_eq!(w.sync().len(), 1);
+        drop(r);
+    });
+
+    let events = recorder.0.lock().unwrap();
+    assert_eq!(
+        *events,
+        [
+            "reader registered readers=1",
+            "published version=1 holdouts=1",
+            "scanned for old values reclaimed=0 pending=1 holdouts=1",
+            "scanned for old values reclaimed=1 pending=0 holdouts=0",
+            "synced returned=1",
+            "reader dropped readers=0 leaked=false",
+        ]
+    );
+}
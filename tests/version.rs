@@ -0,0 +1,48 @@
+#[test]
+fn versions_increment_per_write() {
+    let (mut w, r) = local_rcu::slot(0usize);
+    assert_eq!(w.version(), 0);
+    assert_eq!(r.read().version(), 0);
+
+    for i in 1..=3 {
+        w.write(Box::new(i));
+        assert_eq!(w.version(), i as u64);
+    }
+
+    let g = r.read();
+    assert_eq!(*g, 3);
+    assert_eq!(g.version(), 3);
+}
+
+#[test]
+fn read_if_changed() {
+    let (mut w, r) = local_rcu::slot(0usize);
+    assert!(!r.has_changed());
+    assert!(r.read_if_changed().is_none());
+
+    w.write(Box::new(1));
+    assert!(r.has_changed());
+    assert_eq!(r.read_if_changed().map(|g| *g), Some(1));
+    assert!(!r.has_changed());
+    assert!(r.read_if_changed().is_none());
+}
+
+#[test]
+fn skipped_counts_unobserved_versions() {
+    let (mut w, r) = local_rcu::slot(0usize);
+    let _ = r.read();
+    assert_eq!(r.skipped(), 0);
+
+    w.write(Box::new(1));
+    let _ = r.read();
+    assert_eq!(r.skipped(), 0);
+
+    w.write(Box::new(2));
+    w.write(Box::new(3));
+    w.write(Box::new(4));
+    let _ = r.read();
+    assert_eq!(r.skipped(), 2);
+
+    let _ = r.read();
+    assert_eq!(r.skipped(), 0);
+}
@@ -0,0 +1,38 @@
+#[test]
+fn write_with_reuses_old_value() {
+    let (mut w, r) = local_rcu::slot(vec![0usize]);
+
+    assert!(w.write_with(|v| v.push(1)).is_empty());
+    let first = {
+        let g = r.read();
+        assert_eq!(*g, [0, 1]);
+        &*g as *const Vec<usize>
+    };
+
+    // The initial value is no longer in use, its `Box` is reused.
+    let g = r.read();
+    assert!(w.write_with(|v| v.push(2)).is_empty());
+    assert_eq!(*g, [0, 1]);
+    drop(g);
+    assert_eq!(*r.read(), [0, 1, 2]);
+
+    // `first` was collected by this write and reused for the new value.
+    assert!(w.write_with(|v| v.push(3)).is_empty());
+    let g = r.read();
+    assert_eq!(*g, [0, 1, 2, 3]);
+    assert_eq!(&*g as *const Vec<usize>, first);
+}
+
+#[test]
+fn write_with_returns_extra_values() {
+    let (mut w, r) = local_rcu::slot(0usize);
+
+    let g = r.read();
+    w.write_nosync(Box::new(1));
+    w.write_nosync(Box::new(2));
+    drop(g);
+
+    let old = w.write_with(|v| *v += 1);
+    assert_eq!(old.iter().map(|v| **v).collect::<Vec<_>>(), [1]);
+    assert_eq!(*r.read(), 3);
+}